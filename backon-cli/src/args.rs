@@ -0,0 +1,348 @@
+use std::ffi::OsString;
+use std::fmt;
+use std::time::Duration;
+
+use backon::Backoff;
+use backon::BackoffBuilder;
+use backon::ConstantBuilder;
+use backon::ExponentialBuilder;
+use backon::FibonacciBuilder;
+
+pub const USAGE: &str = "\
+Retry a command until it succeeds.
+
+Usage: backon-cli [OPTIONS] [--] <COMMAND>...
+
+Options:
+  -p, --policy <POLICY>        Backoff policy: exponential, constant or fibonacci [default: exponential]
+      --delay <DURATION>       Delay of constant, or the first delay of exponential and fibonacci
+      --max-delay <DURATION>   Maximum delay of exponential and fibonacci
+      --factor <FACTOR>        Factor of exponential
+  -n, --max-times <TIMES>      Maximum number of retries
+      --total-delay <DURATION> Stop retrying once the total delay would exceed this
+      --jitter                 Add random jitter to every delay
+  -t, --timeout <DURATION>     Kill an attempt that runs longer than this
+  -e, --exit-code <CODE>       Only retry on this exit code, can be repeated
+  -h, --help                   Print help
+  -V, --version                Print version
+
+Durations are numbers with an optional unit: ms, s, m or h (default: s).
+";
+
+/// The backoff policy to retry with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Policy {
+    #[default]
+    Exponential,
+    Constant,
+    Fibonacci,
+}
+
+/// What the command line asks for.
+#[derive(Debug, PartialEq)]
+pub enum Parsed {
+    Run(Args),
+    Help,
+    Version,
+}
+
+/// Arguments to retry a command with.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub policy: Policy,
+    pub delay: Option<Duration>,
+    pub max_delay: Option<Duration>,
+    pub factor: Option<f32>,
+    pub max_times: Option<usize>,
+    pub total_delay: Option<Duration>,
+    pub jitter: bool,
+    pub timeout: Option<Duration>,
+    pub exit_codes: Vec<i32>,
+    pub command: Vec<OsString>,
+}
+
+/// An invalid command line.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Args {
+    /// Build the backoff configured by the arguments.
+    pub fn backoff(&self) -> Box<dyn Backoff> {
+        match self.policy {
+            Policy::Exponential => {
+                let mut builder = ExponentialBuilder::default();
+                if let Some(delay) = self.delay {
+                    builder = builder.with_min_delay(delay);
+                }
+                if let Some(max_delay) = self.max_delay {
+                    builder = builder.with_max_delay(max_delay);
+                }
+                if let Some(factor) = self.factor {
+                    builder = builder.with_factor(factor);
+                }
+                if let Some(max_times) = self.max_times {
+                    builder = builder.with_max_times(max_times);
+                }
+                if self.jitter {
+                    builder = builder.with_jitter();
+                }
+                Box::new(builder.with_total_delay(self.total_delay).build())
+            }
+            Policy::Constant => {
+                let mut builder = ConstantBuilder::default();
+                if let Some(delay) = self.delay {
+                    builder = builder.with_delay(delay);
+                }
+                if let Some(max_times) = self.max_times {
+                    builder = builder.with_max_times(max_times);
+                }
+                if self.jitter {
+                    builder = builder.with_jitter();
+                }
+                Box::new(builder.with_total_delay(self.total_delay).build())
+            }
+            Policy::Fibonacci => {
+                let mut builder = FibonacciBuilder::default();
+                if let Some(delay) = self.delay {
+                    builder = builder.with_min_delay(delay);
+                }
+                if let Some(max_delay) = self.max_delay {
+                    builder = builder.with_max_delay(max_delay);
+                }
+                if let Some(max_times) = self.max_times {
+                    builder = builder.with_max_times(max_times);
+                }
+                if self.jitter {
+                    builder = builder.with_jitter();
+                }
+                Box::new(builder.with_total_delay(self.total_delay).build())
+            }
+        }
+    }
+
+    /// Returns `true` if a command that exited with `code` should be retried.
+    ///
+    /// `code` is `None` if the command was terminated by a signal.
+    pub fn should_retry(&self, code: Option<i32>) -> bool {
+        match code {
+            Some(code) => self.exit_codes.is_empty() || self.exit_codes.contains(&code),
+            None => self.exit_codes.is_empty(),
+        }
+    }
+}
+
+/// Parse the command line, without the program name.
+pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Parsed, Error> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.to_str().filter(|arg| arg.starts_with('-')) else {
+            parsed.command.push(arg);
+            break;
+        };
+        let (flag, inline) = match flag.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (flag, None),
+        };
+        let mut value = || match inline.clone() {
+            Some(value) => Ok(value),
+            None => args
+                .next()
+                .and_then(|value| value.into_string().ok())
+                .ok_or_else(|| Error(format!("{flag} requires a value"))),
+        };
+
+        match flag {
+            "--" => break,
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "-V" | "--version" => return Ok(Parsed::Version),
+            "-p" | "--policy" => {
+                parsed.policy = match value()?.as_str() {
+                    "exponential" => Policy::Exponential,
+                    "constant" => Policy::Constant,
+                    "fibonacci" => Policy::Fibonacci,
+                    policy => return Err(Error(format!("unknown policy: {policy}"))),
+                }
+            }
+            "--delay" => parsed.delay = Some(parse_duration(&value()?)?),
+            "--max-delay" => parsed.max_delay = Some(parse_duration(&value()?)?),
+            "--factor" => parsed.factor = Some(parse_number(flag, &value()?)?),
+            "-n" | "--max-times" => parsed.max_times = Some(parse_number(flag, &value()?)?),
+            "--total-delay" => parsed.total_delay = Some(parse_duration(&value()?)?),
+            "--jitter" => parsed.jitter = true,
+            "-t" | "--timeout" => parsed.timeout = Some(parse_duration(&value()?)?),
+            "-e" | "--exit-code" => parsed.exit_codes.push(parse_number(flag, &value()?)?),
+            _ => return Err(Error(format!("unknown option: {flag}"))),
+        }
+    }
+    parsed.command.extend(args);
+
+    if parsed.command.is_empty() {
+        return Err(Error("no command to run".to_string()));
+    }
+    let unsupported = match parsed.policy {
+        Policy::Exponential => None,
+        Policy::Constant if parsed.max_delay.is_some() => Some("--max-delay"),
+        Policy::Constant | Policy::Fibonacci if parsed.factor.is_some() => Some("--factor"),
+        Policy::Constant | Policy::Fibonacci => None,
+    };
+    if let Some(flag) = unsupported {
+        return Err(Error(format!(
+            "{flag} is not supported by the {:?} policy",
+            parsed.policy
+        )));
+    }
+
+    Ok(Parsed::Run(parsed))
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error(format!("invalid value for {flag}: {value}")))
+}
+
+/// Parse a duration like `500ms`, `1.5s`, `2m` or `1h`; plain numbers are seconds.
+fn parse_duration(value: &str) -> Result<Duration, Error> {
+    let invalid = || Error(format!("invalid duration: {value}"));
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(secs).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Parsed, Error> {
+        parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = parse_args(&[
+            "-p",
+            "constant",
+            "--delay=500ms",
+            "-n",
+            "5",
+            "--jitter",
+            "--timeout",
+            "1m",
+            "-e",
+            "1",
+            "--exit-code",
+            "75",
+            "curl",
+            "-f",
+            "https://example.com",
+        ]);
+
+        assert_eq!(
+            Ok(Parsed::Run(Args {
+                policy: Policy::Constant,
+                delay: Some(Duration::from_millis(500)),
+                max_times: Some(5),
+                jitter: true,
+                timeout: Some(Duration::from_secs(60)),
+                exit_codes: vec![1, 75],
+                command: vec!["curl".into(), "-f".into(), "https://example.com".into()],
+                ..Args::default()
+            })),
+            parsed
+        );
+    }
+
+    #[test]
+    fn test_parse_separator() {
+        let Ok(Parsed::Run(args)) = parse_args(&["--", "--help"]) else {
+            panic!("expected to run a command");
+        };
+
+        assert_eq!(Policy::Exponential, args.policy);
+        assert_eq!(vec![OsString::from("--help")], args.command);
+        assert_eq!(Ok(Parsed::Help), parse_args(&["--help", "--", "ls"]));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Err(Error("no command to run".to_string())),
+            parse_args(&["--jitter"])
+        );
+        assert_eq!(
+            Err(Error("--delay requires a value".to_string())),
+            parse_args(&["--delay"])
+        );
+        assert_eq!(
+            Err(Error("unknown policy: linear".to_string())),
+            parse_args(&["-p", "linear", "ls"])
+        );
+        assert_eq!(
+            Err(Error(
+                "--factor is not supported by the Fibonacci policy".to_string()
+            )),
+            parse_args(&["-p", "fibonacci", "--factor", "3", "ls"])
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Ok(Duration::from_millis(250)), parse_duration("250ms"));
+        assert_eq!(Ok(Duration::from_millis(1500)), parse_duration("1.5s"));
+        assert_eq!(Ok(Duration::from_secs(3)), parse_duration("3"));
+        assert_eq!(Ok(Duration::from_secs(120)), parse_duration("2m"));
+        assert_eq!(Ok(Duration::from_secs(3600)), parse_duration("1h"));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("-1s").is_err());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let args = Args {
+            policy: Policy::Constant,
+            delay: Some(Duration::from_millis(10)),
+            max_times: Some(2),
+            ..Args::default()
+        };
+
+        assert_eq!(
+            vec![Duration::from_millis(10), Duration::from_millis(10)],
+            args.backoff().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let args = Args::default();
+        assert!(args.should_retry(Some(1)));
+        assert!(args.should_retry(None));
+
+        let args = Args {
+            exit_codes: vec![75],
+            ..Args::default()
+        };
+        assert!(args.should_retry(Some(75)));
+        assert!(!args.should_retry(Some(1)));
+        assert!(!args.should_retry(None));
+    }
+}
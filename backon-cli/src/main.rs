@@ -0,0 +1,264 @@
+//! Retry a command until it succeeds.
+//!
+//! `backon-cli` runs a command and retries it with [`backon`] when it exits with a
+//! non-zero code. The stdin, stdout and stderr of the command are passed through.
+//!
+//! ```shell
+//! # Retry up to 5 times with exponential backoff and jitter.
+//! backon-cli --max-times 5 --jitter -- curl -f https://example.com
+//!
+//! # Retry every 2 seconds while the command exits with 75, giving every attempt 30 seconds.
+//! backon-cli -p constant --delay 2s -e 75 --timeout 30s -- ./deploy.sh
+//! ```
+//!
+//! The exit code of `backon-cli` is the exit code of the last attempt, `128 + signal`
+//! if it was killed by a signal, `124` if it timed out, or `126`/`127` if the command
+//! could not be run.
+
+mod args;
+
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::process::Child;
+use std::process::Command;
+use std::process::ExitCode;
+use std::process::ExitStatus;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use backon::BlockingRetryable;
+
+use crate::args::Args;
+use crate::args::Parsed;
+
+/// The interval to check whether an attempt with a timeout has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Failure is the reason an attempt failed.
+#[derive(Debug)]
+enum Failure {
+    /// The command exited with a non-zero status.
+    Exit(ExitStatus),
+    /// The command was killed after running longer than the timeout.
+    Timeout(Duration),
+    /// The command could not be run.
+    Io(io::Error),
+}
+
+impl Failure {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Failure::Exit(status) => match status.code() {
+                // Codes that don't fit in a byte, like 256 on Windows, must not wrap to success.
+                Some(code) => u8::try_from(code)
+                    .ok()
+                    .filter(|code| *code != 0)
+                    .map_or(ExitCode::FAILURE, ExitCode::from),
+                None => signal_exit_code(status),
+            },
+            Failure::Timeout(_) => ExitCode::from(124),
+            Failure::Io(err) if err.kind() == io::ErrorKind::NotFound => ExitCode::from(127),
+            Failure::Io(_) => ExitCode::from(126),
+        }
+    }
+}
+
+/// Returns `128 + signal` for a command killed by a signal, like shells do.
+#[cfg(unix)]
+fn signal_exit_code(status: &ExitStatus) -> ExitCode {
+    use std::os::unix::process::ExitStatusExt;
+
+    status
+        .signal()
+        .and_then(|signal| u8::try_from(128 + signal).ok())
+        .map_or(ExitCode::FAILURE, ExitCode::from)
+}
+
+#[cfg(not(unix))]
+fn signal_exit_code(_: &ExitStatus) -> ExitCode {
+    ExitCode::FAILURE
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Exit(status) => write!(f, "command failed with {status}"),
+            Failure::Timeout(timeout) => write!(f, "command timed out after {timeout:?}"),
+            Failure::Io(err) => write!(f, "failed to run command: {err}"),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match args::parse(std::env::args_os().skip(1)) {
+        Ok(Parsed::Run(args)) => args,
+        Ok(Parsed::Help) => {
+            print!("{}", args::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Parsed::Version) => {
+            println!("backon-cli {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("backon-cli: {err}\n\nRun `backon-cli --help` for usage.");
+            return ExitCode::from(2);
+        }
+    };
+
+    match retry(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("backon-cli: {err}, giving up");
+            err.exit_code()
+        }
+    }
+}
+
+/// Run the command until it succeeds or the backoff gives up.
+fn retry(args: &Args) -> Result<(), Failure> {
+    (|| run(&args.command, args.timeout))
+        .retry(args.backoff())
+        .when(|err| match err {
+            Failure::Exit(status) => args.should_retry(status.code()),
+            Failure::Timeout(_) => true,
+            Failure::Io(_) => false,
+        })
+        .notify(|err, dur| eprintln!("backon-cli: {err}, retrying in {dur:?}"))
+        .call()
+}
+
+/// Run the command once, killing it if it runs longer than `timeout`.
+fn run(command: &[OsString], timeout: Option<Duration>) -> Result<(), Failure> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .spawn()
+        .map_err(Failure::Io)?;
+
+    let status = match timeout {
+        None => child.wait().map_err(Failure::Io)?,
+        Some(timeout) => wait_timeout(&mut child, timeout)?,
+    };
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Failure::Exit(status))
+    }
+}
+
+fn wait_timeout(child: &mut Child, timeout: Duration) -> Result<ExitStatus, Failure> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().map_err(Failure::Io)? {
+            return Ok(status);
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            // The child may have exited just now, which is fine to ignore.
+            let _ = child.kill();
+            child.wait().map_err(Failure::Io)?;
+            return Err(Failure::Timeout(timeout));
+        }
+        thread::sleep(POLL_INTERVAL.min(timeout - elapsed));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::args::Policy;
+
+    fn sh(script: &str) -> Vec<OsString> {
+        vec!["sh".into(), "-c".into(), script.into()]
+    }
+
+    fn args(command: Vec<OsString>) -> Args {
+        Args {
+            policy: Policy::Constant,
+            delay: Some(Duration::from_millis(1)),
+            max_times: Some(2),
+            command,
+            ..Args::default()
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        assert!(run(&sh("exit 0"), None).is_ok());
+        assert!(matches!(
+            run(&sh("exit 3"), None),
+            Err(Failure::Exit(status)) if status.code() == Some(3)
+        ));
+        assert!(matches!(
+            run(&["backon-cli-not-found".into()], None),
+            Err(Failure::Io(err)) if err.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn test_exit_code() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let exit_code = |raw| Failure::Exit(ExitStatus::from_raw(raw)).exit_code();
+
+        // The raw wait status keeps the exit code in the second byte.
+        assert_eq!(ExitCode::from(3), exit_code(3 << 8));
+        // Killed by SIGKILL.
+        assert_eq!(ExitCode::from(137), exit_code(9));
+    }
+
+    #[test]
+    fn test_run_timeout() {
+        let start = Instant::now();
+        let result = run(&sh("sleep 5"), Some(Duration::from_millis(50)));
+
+        assert!(matches!(result, Err(Failure::Timeout(_))));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    /// Run `script` with retries, returning the result and the number of attempts.
+    fn retry_counted(
+        name: &str,
+        script: &str,
+        exit_codes: Vec<i32>,
+    ) -> (Result<(), Failure>, usize) {
+        let path = std::env::temp_dir().join(format!("backon-cli-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Every attempt appends a line to `$FILE`.
+        let script = format!("FILE={}; echo >> \"$FILE\"; {script}", path.display());
+        let args = Args {
+            exit_codes,
+            ..args(sh(&script))
+        };
+        let result = retry(&args);
+        let attempts = std::fs::read_to_string(&path).unwrap().lines().count();
+        let _ = std::fs::remove_file(&path);
+
+        (result, attempts)
+    }
+
+    #[test]
+    fn test_retry() {
+        // Fails on the first attempt and succeeds on the second one.
+        let (result, attempts) = retry_counted("retry", "test $(wc -l < \"$FILE\") -ge 2", vec![]);
+
+        assert!(result.is_ok());
+        assert_eq!(2, attempts);
+    }
+
+    #[test]
+    fn test_retry_exit_codes() {
+        let (result, attempts) = retry_counted("exit-codes", "exit 1", vec![75]);
+        assert!(matches!(result, Err(Failure::Exit(status)) if status.code() == Some(1)));
+        assert_eq!(1, attempts);
+
+        let (result, attempts) = retry_counted("exit-codes-match", "exit 75", vec![75]);
+        assert!(matches!(result, Err(Failure::Exit(status)) if status.code() == Some(75)));
+        assert_eq!(3, attempts);
+    }
+}
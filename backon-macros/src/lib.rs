@@ -0,0 +1,106 @@
+//! Macros for [backon](https://docs.rs/backon).
+//!
+//! This crate is not intended to be used directly, please enable the `macros`
+//! feature of `backon` and use the macros re-exported by `backon` instead.
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+
+mod retry;
+mod retryable_error;
+
+/// Retry a function with backoff.
+///
+/// The annotated `async fn` is rewritten into a call through `Retryable::retry`,
+/// and a plain `fn` into `BlockingRetryable::retry(...).call()`. The function
+/// must return a `Result`.
+///
+/// # Arguments
+///
+/// - `exponential(...)`, `constant(...)` or `fibonacci(...)`: the backoff to use,
+///   default to `exponential`. Options are forwarded to the builder: `max_times = 5`
+///   calls `.with_max_times(5)` and `jitter` calls `.with_jitter()`. Options that
+///   already start with `with_` or `without_` are called as is.
+/// - `backoff = expr`: use any `BackoffBuilder` expression instead.
+/// - `when = expr`: the conditions for retrying, see `Retry::when`.
+/// - `notify = expr`: the function to notify for retry attempts, see `Retry::notify`.
+/// - `adjust = expr`: the function to adjust the backoff, see `Retry::adjust`.
+/// - `sleep = expr`: the sleeper to use, see `Retry::sleep`.
+///
+/// # Receivers and arguments
+///
+/// - Owned arguments are cloned for every attempt, so they must implement `Clone`.
+/// - Shared references, including `&self`, are captured as is.
+/// - `&mut self` and `&mut` arguments of an `async fn` are passed through
+///   `RetryableWithContext`, so they can be used across attempts.
+/// - By-value `self` is not supported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+///
+/// fn is_transient(err: &anyhow::Error) -> bool {
+///     err.to_string() == "EOF"
+/// }
+///
+/// async fn get(url: &str) -> Result<String> {
+///     Ok(format!("hello from {url}"))
+/// }
+///
+/// struct Client {
+///     requests: usize,
+/// }
+///
+/// impl Client {
+///     #[backon::retry(exponential(max_times = 5, jitter), when = is_transient)]
+///     async fn fetch(&mut self, url: String) -> Result<String> {
+///         self.requests += 1;
+///         get(&url).await
+///     }
+/// }
+///
+/// #[backon::retry(constant(max_times = 3))]
+/// fn read(path: &str) -> std::io::Result<String> {
+///     std::fs::read_to_string(path)
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn retry(args: TokenStream, input: TokenStream) -> TokenStream {
+    retry::expand(args.into(), input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `RetryableError` for an enum.
+///
+/// Variants marked with `#[transient]` are retryable, variants marked with
+/// `#[permanent]` or not marked at all are not. `retry_after` is not overridden,
+/// implement `RetryableError` by hand if the error carries a delay.
+///
+/// # Examples
+///
+/// ```
+/// use backon::RetryableError;
+///
+/// #[derive(Debug, RetryableError)]
+/// enum Error {
+///     #[transient]
+///     Timeout,
+///     #[transient]
+///     Io(std::io::Error),
+///     #[permanent]
+///     InvalidInput { field: String },
+///     Unknown,
+/// }
+///
+/// assert!(Error::Timeout.is_retryable());
+/// assert!(!Error::Unknown.is_retryable());
+/// ```
+#[proc_macro_derive(RetryableError, attributes(transient, permanent))]
+pub fn derive_retryable_error(input: TokenStream) -> TokenStream {
+    retryable_error::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
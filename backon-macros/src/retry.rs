@@ -0,0 +1,340 @@
+use proc_macro2::Ident;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use proc_macro2::TokenTree;
+use quote::quote;
+use quote::ToTokens;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::Error;
+use syn::Expr;
+use syn::FnArg;
+use syn::ItemFn;
+use syn::Meta;
+use syn::Pat;
+use syn::ReturnType;
+use syn::Token;
+use syn::Type;
+
+/// The name used in place of `self` when `&mut self` is passed as context.
+const SELF_IDENT: &str = "__backon_self";
+
+/// Arguments of the `#[retry]` attribute.
+#[derive(Default)]
+struct Args {
+    backoff: Option<TokenStream>,
+    when: Option<Expr>,
+    notify: Option<Expr>,
+    adjust: Option<Expr>,
+    sleep: Option<Expr>,
+}
+
+impl Args {
+    fn parse(args: TokenStream) -> syn::Result<Self> {
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(args)?;
+
+        let mut parsed = Args::default();
+        for meta in metas {
+            let name = meta
+                .path()
+                .get_ident()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+
+            match (name.as_str(), meta) {
+                ("exponential" | "constant" | "fibonacci", meta) => {
+                    let builder = parse_builder(&name, meta)?;
+                    set_once(&mut parsed.backoff, builder, "backoff")?;
+                }
+                ("backoff", Meta::NameValue(nv)) => {
+                    set_once(&mut parsed.backoff, nv.value.into_token_stream(), "backoff")?
+                }
+                ("when", Meta::NameValue(nv)) => set_once(&mut parsed.when, nv.value, "when")?,
+                ("notify", Meta::NameValue(nv)) => {
+                    set_once(&mut parsed.notify, nv.value, "notify")?
+                }
+                ("adjust", Meta::NameValue(nv)) => {
+                    set_once(&mut parsed.adjust, nv.value, "adjust")?
+                }
+                ("sleep", Meta::NameValue(nv)) => set_once(&mut parsed.sleep, nv.value, "sleep")?,
+                (_, meta) => {
+                    return Err(Error::new_spanned(
+                        meta,
+                        "unknown argument, expected one of `exponential(..)`, `constant(..)`, \
+                         `fibonacci(..)`, `backoff = ..`, `when = ..`, `notify = ..`, \
+                         `adjust = ..` or `sleep = ..`",
+                    ))
+                }
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn set_once<T: Spanned>(slot: &mut Option<T>, value: T, name: &str) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(Error::new(
+            value.span(),
+            format!("`{name}` has been specified more than once"),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Build the backoff builder expression like `ExponentialBuilder::new().with_max_times(5)`.
+fn parse_builder(name: &str, meta: Meta) -> syn::Result<TokenStream> {
+    let builder = match name {
+        "exponential" => quote!(::backon::ExponentialBuilder),
+        "constant" => quote!(::backon::ConstantBuilder),
+        "fibonacci" => quote!(::backon::FibonacciBuilder),
+        _ => unreachable!("builder name must be valid"),
+    };
+
+    let options = match meta {
+        Meta::Path(_) => Punctuated::new(),
+        Meta::List(list) => {
+            list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?
+        }
+        Meta::NameValue(nv) => {
+            return Err(Error::new_spanned(
+                nv,
+                format!("expected `{name}` or `{name}(..)`"),
+            ))
+        }
+    };
+
+    let mut methods = TokenStream::new();
+    for option in options {
+        let ident = option
+            .path()
+            .get_ident()
+            .ok_or_else(|| Error::new_spanned(option.path(), "expected an identifier"))?;
+        let method = builder_method(ident);
+        match option {
+            Meta::Path(_) => methods.extend(quote!(.#method())),
+            Meta::NameValue(nv) => {
+                let value = nv.value;
+                methods.extend(quote!(.#method(#value)))
+            }
+            Meta::List(list) => {
+                return Err(Error::new_spanned(
+                    list,
+                    "expected `option` or `option = value`",
+                ))
+            }
+        }
+    }
+
+    Ok(quote!(#builder::new() #methods))
+}
+
+/// Map option `max_times` to builder method `with_max_times`.
+fn builder_method(ident: &Ident) -> Ident {
+    let name = ident.to_string();
+    if name.starts_with("with_") || name.starts_with("without_") {
+        ident.clone()
+    } else {
+        Ident::new(&format!("with_{name}"), ident.span())
+    }
+}
+
+/// A `&mut` receiver or argument which is passed as context.
+struct ContextArg {
+    /// The pattern used inside the retried closure.
+    pat: TokenStream,
+    /// The name used inside the retried closure.
+    name: TokenStream,
+    /// The expression passed into the context.
+    value: TokenStream,
+    ty: TokenStream,
+}
+
+pub fn expand(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let args = Args::parse(args)?;
+    let mut item: ItemFn = syn::parse2(input)?;
+
+    let ret = match &item.sig.output {
+        ReturnType::Type(_, ty) => ty.clone(),
+        ReturnType::Default => {
+            return Err(Error::new_spanned(
+                &item.sig,
+                "retried function must return a `Result`",
+            ))
+        }
+    };
+    let is_async = item.sig.asyncness.is_some();
+
+    let mut clones = TokenStream::new();
+    let mut contexts = Vec::new();
+    for input in item.sig.inputs.iter_mut() {
+        match input {
+            FnArg::Receiver(recv) => match (&recv.reference, &recv.mutability) {
+                (None, _) => {
+                    return Err(Error::new_spanned(
+                        recv,
+                        "by-value `self` is not supported, use `&self` or `&mut self` instead",
+                    ))
+                }
+                (Some(_), Some(_)) => {
+                    let ident = Ident::new(SELF_IDENT, Span::call_site());
+                    contexts.push(ContextArg {
+                        pat: quote!(#ident),
+                        name: quote!(#ident),
+                        value: quote!(self),
+                        ty: quote!(&mut Self),
+                    });
+                }
+                (Some(_), None) => {}
+            },
+            FnArg::Typed(arg) => {
+                let pat = match arg.pat.as_mut() {
+                    Pat::Wild(_) => continue,
+                    Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => pat,
+                    pat => {
+                        return Err(Error::new_spanned(
+                            pat,
+                            "only identifier patterns are supported in retried functions",
+                        ))
+                    }
+                };
+                let ident = &pat.ident;
+
+                match arg.ty.as_ref() {
+                    // Blocking functions can capture `&mut` arguments directly.
+                    Type::Reference(r) if r.mutability.is_some() && is_async => {
+                        // The outer binding is only moved into the context, move `mut` into the closure.
+                        let mutability = pat.mutability.take();
+                        let ty = &arg.ty;
+                        contexts.push(ContextArg {
+                            pat: quote!(#mutability #ident),
+                            name: quote!(#ident),
+                            value: quote!(#ident),
+                            ty: quote!(#ty),
+                        });
+                    }
+                    Type::Reference(_) => {}
+                    _ => {
+                        // The outer binding is only cloned from, move `mut` into the clone.
+                        let mutability = pat.mutability.take();
+                        clones.extend(quote! {
+                            let #mutability #ident = ::core::clone::Clone::clone(&#ident);
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let builder = args
+        .backoff
+        .unwrap_or_else(|| quote!(::backon::ExponentialBuilder::default()));
+    let sleep = args.sleep.map(|v| quote!(.sleep(#v)));
+    let when = args.when.map(|v| quote!(.when(#v)));
+    let notify = args.notify.map(|v| quote!(.notify(#v)));
+    let adjust = args.adjust.map(|v| quote!(.adjust(#v)));
+
+    let block = &item.block;
+    let body = match (is_async, contexts.is_empty()) {
+        (true, true) => {
+            quote! {{
+                ::backon::Retryable::retry(
+                    || {
+                        #clones
+                        async move {
+                            let __backon_result: #ret = #block;
+                            __backon_result
+                        }
+                    },
+                    #builder,
+                )
+                #sleep
+                #when
+                #notify
+                #adjust
+                .await
+            }}
+        }
+        (true, false) => {
+            let pats = contexts.iter().map(|c| &c.pat);
+            let names = contexts.iter().map(|c| &c.name);
+            let values = contexts.iter().map(|c| &c.value);
+            let tys = contexts.iter().map(|c| &c.ty);
+            let block = rename_self(block.to_token_stream());
+            quote! {{
+                let (_, __backon_result) = ::backon::RetryableWithContext::retry(
+                    ::backon::__private::with_context::<(#(#tys,)*), _, _>(
+                        |(#(#pats,)*)| {
+                            #clones
+                            async move {
+                                let __backon_result: #ret = async #block.await;
+                                ((#(#names,)*), __backon_result)
+                            }
+                        },
+                    ),
+                    #builder,
+                )
+                #sleep
+                .context((#(#values,)*))
+                #when
+                #notify
+                #adjust
+                .await;
+                __backon_result
+            }}
+        }
+        (false, _) => {
+            quote! {{
+                ::backon::BlockingRetryable::retry(
+                    || -> #ret {
+                        #clones
+                        #block
+                    },
+                    #builder,
+                )
+                #sleep
+                #when
+                #notify
+                #adjust
+                .call()
+            }}
+        }
+    };
+
+    item.block = syn::parse2(body)?;
+    Ok(item.into_token_stream())
+}
+
+/// Replace `self` with [`SELF_IDENT`] so that `&mut self` can be passed as context.
+///
+/// `self::path` is kept untouched.
+fn rename_self(input: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+
+    let mut output = TokenStream::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        let token = match token {
+            TokenTree::Ident(ident) if ident == "self" => {
+                let is_path = matches!(
+                    tokens.get(idx + 1),
+                    Some(TokenTree::Punct(p)) if p.as_char() == ':'
+                );
+                if is_path {
+                    token.clone()
+                } else {
+                    TokenTree::Ident(Ident::new(SELF_IDENT, ident.span()))
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut renamed =
+                    proc_macro2::Group::new(group.delimiter(), rename_self(group.stream()));
+                renamed.set_span(group.span());
+                TokenTree::Group(renamed)
+            }
+            _ => token.clone(),
+        };
+        output.extend(Some(token));
+    }
+    output
+}
@@ -0,0 +1,62 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Meta;
+
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = syn::parse2(input)?;
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`RetryableError` can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut arms = TokenStream::new();
+    for variant in &data.variants {
+        let mut retryable = None;
+        for attr in &variant.attrs {
+            let value = if attr.path().is_ident("transient") {
+                true
+            } else if attr.path().is_ident("permanent") {
+                false
+            } else {
+                continue;
+            };
+            if !matches!(attr.meta, Meta::Path(_)) {
+                return Err(Error::new_spanned(
+                    attr,
+                    "expected `#[transient]` or `#[permanent]`",
+                ));
+            }
+            if retryable.replace(value).is_some() {
+                return Err(Error::new_spanned(
+                    attr,
+                    "a variant can only be marked once as `#[transient]` or `#[permanent]`",
+                ));
+            }
+        }
+
+        // Unmarked variants are permanent, we never retry errors we know nothing about.
+        let retryable = retryable.unwrap_or(false);
+        let ident = &variant.ident;
+        arms.extend(quote!(Self::#ident { .. } => #retryable,));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::backon::RetryableError for #name #ty_generics #where_clause {
+            fn is_retryable(&self) -> bool {
+                match *self {
+                    #arms
+                }
+            }
+        }
+    })
+}
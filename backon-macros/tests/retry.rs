@@ -0,0 +1,162 @@
+use std::cell::Cell;
+use std::future::ready;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use anyhow::Result;
+
+fn no_sleep(_: Duration) -> std::future::Ready<()> {
+    ready(())
+}
+
+fn is_retryable(err: &anyhow::Error) -> bool {
+    err.to_string() == "retryable"
+}
+
+struct Counter {
+    calls: usize,
+    fail_until: usize,
+}
+
+impl Counter {
+    #[backon::retry(constant(delay = Duration::from_millis(1), max_times = 5), sleep = no_sleep)]
+    async fn fetch(&mut self, prefix: String) -> Result<String> {
+        self.calls += 1;
+        if self.calls < self.fail_until {
+            return Err(anyhow!("retryable"));
+        }
+        Ok(format!("{prefix}-{}", self.calls))
+    }
+
+    #[backon::retry(constant(max_times = 5), sleep = no_sleep, adjust = |_, _| None)]
+    async fn fetch_once(&mut self) -> Result<usize> {
+        self.calls += 1;
+        Err(anyhow!("retryable"))
+    }
+
+    #[backon::retry(constant(max_times = 5), sleep = no_sleep, when = is_retryable)]
+    async fn fetch_shared(&self, calls: &Cell<usize>) -> Result<usize> {
+        calls.set(calls.get() + 1);
+        if calls.get() < self.fail_until {
+            return Err(anyhow!("retryable"));
+        }
+        Ok(calls.get())
+    }
+
+    #[backon::retry(constant(max_times = 5), sleep = |_| {})]
+    fn fetch_blocking(&mut self, mut suffix: String) -> Result<String> {
+        self.calls += 1;
+        suffix.push('!');
+        if self.calls < self.fail_until {
+            return Err(anyhow!("retryable"));
+        }
+        Ok(suffix)
+    }
+}
+
+#[backon::retry(exponential(min_delay = Duration::from_millis(1), max_times = 2, jitter), sleep = no_sleep, when = is_retryable)]
+async fn always_fail(mut calls: &mut usize, message: &str) -> Result<()> {
+    let counter = &mut calls;
+    **counter += 1;
+    Err(anyhow!(message.to_string()))
+}
+
+#[backon::retry(backoff = backon::ConstantBuilder::default().with_max_times(2), sleep = |_| {})]
+fn always_fail_blocking(calls: &Cell<usize>) -> Result<()> {
+    calls.set(calls.get() + 1);
+    Err(anyhow!("retryable"))
+}
+
+#[backon::retry(constant(max_times = 5), sleep = |_| {}, adjust = |_, _| None)]
+fn never_retry_blocking(calls: &Cell<usize>) -> Result<()> {
+    calls.set(calls.get() + 1);
+    Err(anyhow!("retryable"))
+}
+
+#[tokio::test]
+async fn test_retry_mut_self() {
+    let mut counter = Counter {
+        calls: 0,
+        fail_until: 3,
+    };
+
+    let result = counter.fetch("hello".to_string()).await.unwrap();
+
+    assert_eq!("hello-3", result);
+    assert_eq!(3, counter.calls);
+}
+
+#[tokio::test]
+async fn test_retry_mut_self_adjust() {
+    let mut counter = Counter {
+        calls: 0,
+        fail_until: 3,
+    };
+
+    let result = counter.fetch_once().await;
+
+    assert!(result.is_err());
+    assert_eq!(1, counter.calls);
+}
+
+#[tokio::test]
+async fn test_retry_shared_self() {
+    let counter = Counter {
+        calls: 0,
+        fail_until: 2,
+    };
+    let calls = Cell::new(0);
+
+    let result = counter.fetch_shared(&calls).await.unwrap();
+
+    assert_eq!(2, result);
+}
+
+#[tokio::test]
+async fn test_retry_mut_arg() {
+    let mut calls = 0;
+
+    let result = always_fail(&mut calls, "retryable").await;
+    assert_eq!("retryable", result.unwrap_err().to_string());
+    assert_eq!(3, calls);
+
+    // Not retryable, called only once.
+    let mut calls = 0;
+    let result = always_fail(&mut calls, "fatal").await;
+    assert_eq!("fatal", result.unwrap_err().to_string());
+    assert_eq!(1, calls);
+}
+
+#[test]
+fn test_retry_blocking() {
+    let mut counter = Counter {
+        calls: 0,
+        fail_until: 3,
+    };
+
+    // The argument is cloned for every attempt.
+    let result = counter.fetch_blocking("hello".to_string()).unwrap();
+
+    assert_eq!("hello!", result);
+    assert_eq!(3, counter.calls);
+}
+
+#[test]
+fn test_retry_blocking_exhausted() {
+    let calls = Cell::new(0);
+
+    let result = always_fail_blocking(&calls);
+
+    assert!(result.is_err());
+    assert_eq!(3, calls.get());
+}
+
+#[test]
+fn test_retry_blocking_adjust() {
+    let calls = Cell::new(0);
+
+    let result = never_retry_blocking(&calls);
+
+    assert!(result.is_err());
+    assert_eq!(1, calls.get());
+}
@@ -0,0 +1,63 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use backon::BlockingRetryable;
+use backon::ConstantBuilder;
+use backon::RetryableError;
+
+#[derive(Debug, RetryableError)]
+enum Error {
+    #[transient]
+    Timeout,
+    #[transient]
+    Io(#[allow(dead_code)] std::io::Error),
+    #[permanent]
+    InvalidInput {
+        #[allow(dead_code)]
+        field: String,
+    },
+    Unknown,
+}
+
+#[derive(Debug, RetryableError)]
+enum GenericError<T> {
+    #[transient]
+    Busy(PhantomData<T>),
+    #[permanent]
+    Closed,
+}
+
+#[test]
+fn test_derive() {
+    assert!(Error::Timeout.is_retryable());
+    assert!(Error::Io(std::io::ErrorKind::TimedOut.into()).is_retryable());
+    assert!(!Error::InvalidInput {
+        field: "name".to_string()
+    }
+    .is_retryable());
+    assert!(!Error::Unknown.is_retryable());
+    assert_eq!(None, Error::Timeout.retry_after());
+
+    assert!(GenericError::<u8>::Busy(PhantomData).is_retryable());
+    assert!(!GenericError::<u8>::Closed.is_retryable());
+}
+
+#[test]
+fn test_derive_when_retryable() {
+    let calls = Cell::new(0);
+    let result = (|| {
+        calls.set(calls.get() + 1);
+        if calls.get() < 3 {
+            Err(Error::Timeout)
+        } else {
+            Err(Error::Unknown)
+        }
+    })
+    .retry(ConstantBuilder::default().with_max_times(5))
+    .sleep(|_| {})
+    .when_retryable()
+    .call();
+
+    assert!(matches!(result, Err::<(), _>(Error::Unknown)));
+    assert_eq!(3, calls.get());
+}
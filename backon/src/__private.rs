@@ -0,0 +1,10 @@
+//! Items used by the code generated by `backon-macros`, not public API.
+
+/// Pin down the context type of a closure passed to [`RetryableWithContext`][crate::RetryableWithContext].
+///
+/// Without an expected signature, a closure taking `&mut T` is inferred to be
+/// higher-ranked over the lifetime, which makes it impossible to return a
+/// future borrowing the context.
+pub fn with_context<Ctx, Fut, F: FnMut(Ctx) -> Fut>(f: F) -> F {
+    f
+}
@@ -7,6 +7,18 @@ use core::time::Duration;
 pub trait Backoff: Iterator<Item = Duration> + Send + Sync + Unpin {}
 impl<T> Backoff for T where T: Iterator<Item = Duration> + Send + Sync + Unpin {}
 
+/// ResettableBackoff is a [`Backoff`] that can be reset to its initial state.
+///
+/// This allows long-lived loops, like reconnecting to a server, to reuse the same
+/// backoff after a successful operation instead of building a new one.
+pub trait ResettableBackoff: Backoff {
+    /// Reset the backoff to the state it was built with.
+    ///
+    /// Internal state such as the jitter random number generator is kept, so a seeded
+    /// backoff keeps producing the same sequence across resets.
+    fn reset(&mut self);
+}
+
 /// BackoffBuilder is utilized to construct a new backoff.
 pub trait BackoffBuilder: Send + Sync + Unpin {
     /// The associated backoff returned by this builder.
@@ -24,6 +36,22 @@ impl<B: Backoff> BackoffBuilder for B {
     }
 }
 
+/// ErrorBackoff is a backoff that can pick the next delay based on the error.
+///
+/// Every [`Backoff`] is an `ErrorBackoff` which ignores the error. Use
+/// [`Retry::backoff_for`](crate::Retry::backoff_for) to build one that uses
+/// different backoffs for different classes of errors.
+pub trait ErrorBackoff<E>: Send + Sync + Unpin {
+    /// Returns the delay before retrying after `err`, or `None` to give up.
+    fn next_for(&mut self, err: &E) -> Option<Duration>;
+}
+
+impl<E, B: Backoff> ErrorBackoff<E> for B {
+    fn next_for(&mut self, _: &E) -> Option<Duration> {
+        self.next()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,166 @@
+use core::time::Duration;
+use std::time::Instant;
+
+use crate::backoff::ResettableBackoff;
+
+/// AutoResetBackoff wraps a [`ResettableBackoff`] and resets it after a quiet period.
+///
+/// The backoff is considered quiet when no delay has been requested for
+/// `quiet_period` after the previous delay elapsed, which means the operation
+/// has been succeeding in between. The next delay then starts from the beginning
+/// of the inner backoff again.
+///
+/// This is useful for long-lived loops like reconnecting to a server: a
+/// connection that stayed up for a while should not inherit the delays of
+/// the failures that happened long before.
+///
+/// # Examples
+///
+/// ```no_run
+/// use core::time::Duration;
+///
+/// use backon::AutoResetBackoff;
+/// use backon::BackoffBuilder;
+/// use backon::ExponentialBuilder;
+///
+/// let mut backoff = AutoResetBackoff::new(
+///     ExponentialBuilder::default().without_max_times().build(),
+///     Duration::from_secs(60),
+/// );
+///
+/// loop {
+///     // connect and serve until the connection breaks.
+///
+///     match backoff.next() {
+///         Some(dur) => std::thread::sleep(dur),
+///         None => break,
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AutoResetBackoff<B: ResettableBackoff> {
+    inner: B,
+    quiet_period: Duration,
+
+    /// The time at which the last delay will have elapsed.
+    last_wakeup: Option<Instant>,
+    /// Reads the current time, replaced by tests to control the clock.
+    now: fn() -> Instant,
+}
+
+impl<B: ResettableBackoff> AutoResetBackoff<B> {
+    /// Create a new `AutoResetBackoff` that resets `inner` after `quiet_period`.
+    pub fn new(inner: B, quiet_period: Duration) -> Self {
+        Self {
+            inner,
+            quiet_period,
+            last_wakeup: None,
+            now: Instant::now,
+        }
+    }
+
+    /// Consume the wrapper, returning the inner backoff.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: ResettableBackoff> Iterator for AutoResetBackoff<B> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let now = (self.now)();
+        if let Some(last_wakeup) = self.last_wakeup {
+            if now.saturating_duration_since(last_wakeup) >= self.quiet_period {
+                self.inner.reset();
+            }
+        }
+
+        let dur = self.inner.next();
+        self.last_wakeup = match dur {
+            Some(dur) => now.checked_add(dur),
+            // The caller gives up now, start counting the quiet period from here.
+            None => Some(now),
+        };
+        dur
+    }
+}
+
+impl<B: ResettableBackoff> ResettableBackoff for AutoResetBackoff<B> {
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.last_wakeup = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    use super::*;
+    use crate::BackoffBuilder;
+    use crate::ConstantBuilder;
+    use crate::ExponentialBuilder;
+
+    std::thread_local! {
+        static NOW: Cell<Option<Instant>> = const { Cell::new(None) };
+    }
+
+    /// A clock that only moves when the test calls [`advance`].
+    fn now() -> Instant {
+        NOW.with(|now| *now.get().get_or_insert_with(Instant::now))
+    }
+
+    fn advance(dur: Duration) {
+        let next = now() + dur;
+        NOW.with(|now| now.set(Some(next)));
+    }
+
+    #[test]
+    fn test_auto_reset_after_quiet_period() {
+        let mut backoff = AutoResetBackoff::new(
+            ExponentialBuilder::default().build(),
+            Duration::from_secs(60),
+        );
+        backoff.now = now;
+
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next());
+        advance(Duration::from_secs(1));
+        assert_eq!(Some(Duration::from_secs(2)), backoff.next());
+
+        // Quiet for 59s after the 2s delay elapsed, not long enough.
+        advance(Duration::from_secs(2 + 59));
+        assert_eq!(Some(Duration::from_secs(4)), backoff.next());
+
+        // Quiet for 60s after the 4s delay elapsed, the backoff starts over.
+        advance(Duration::from_secs(4 + 60));
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next());
+    }
+
+    #[test]
+    fn test_auto_reset_not_quiet() {
+        let mut backoff = AutoResetBackoff::new(
+            ConstantBuilder::default().with_max_times(2).build(),
+            Duration::from_secs(60),
+        );
+
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next());
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next());
+        assert_eq!(None, backoff.next());
+    }
+
+    #[test]
+    fn test_auto_reset_manual() {
+        let mut backoff = AutoResetBackoff::new(
+            ConstantBuilder::default().with_max_times(1).build(),
+            Duration::from_secs(60),
+        );
+
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next());
+        assert_eq!(None, backoff.next());
+
+        backoff.reset();
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next());
+    }
+}
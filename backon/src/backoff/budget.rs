@@ -0,0 +1,60 @@
+use core::time::Duration;
+
+/// Budget tracks the attempts and the cumulative delay of a backoff against its limits.
+///
+/// It's shared by all builtin backoffs so `max_times` and `total_delay` behave the same
+/// regardless of how the delays are computed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Budget {
+    max_times: Option<usize>,
+    total_delay: Option<Duration>,
+
+    attempts: usize,
+    cumulative_delay: Duration,
+}
+
+impl Budget {
+    pub(crate) fn new(max_times: Option<usize>, total_delay: Option<Duration>) -> Self {
+        #[cfg(feature = "std")]
+        let total_delay = crate::backoff::schedule::preview_total_delay(total_delay);
+
+        Self {
+            max_times,
+            total_delay,
+
+            attempts: 0,
+            cumulative_delay: Duration::ZERO,
+        }
+    }
+
+    /// Count another attempt, returns `false` if the maximum number of attempts is reached.
+    pub(crate) fn try_attempt(&mut self) -> bool {
+        if self.attempts >= self.max_times.unwrap_or(usize::MAX) {
+            return false;
+        }
+        self.attempts += 1;
+        true
+    }
+
+    /// Spend `delay`, returns `false` if the cumulative delay would exceed the total delay.
+    pub(crate) fn try_spend(&mut self, delay: Duration) -> bool {
+        if let Some(total_delay) = self.total_delay {
+            let cumulative_delay = self.cumulative_delay.saturating_add(delay);
+            if cumulative_delay > total_delay {
+                return false;
+            }
+            self.cumulative_delay = cumulative_delay;
+        }
+        true
+    }
+
+    /// Returns the number of attempts counted so far.
+    pub(crate) fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.attempts = 0;
+        self.cumulative_delay = Duration::ZERO;
+    }
+}
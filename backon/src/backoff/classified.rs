@@ -0,0 +1,83 @@
+use core::fmt;
+use core::time::Duration;
+
+use crate::backoff::ErrorBackoff;
+
+/// ClassifiedBackoff sends errors of one class to their own backoff.
+///
+/// Errors matching `class` use `backoff`, all others fall through to `inner`.
+/// Every backoff keeps its own attempt counter and limit, so a burst of errors
+/// in one class doesn't exhaust the retries of another.
+///
+/// This type is created by [`Retry::backoff_for`](crate::Retry::backoff_for)
+/// and [`BlockingRetry::backoff_for`](crate::BlockingRetry::backoff_for).
+pub struct ClassifiedBackoff<I, P, B> {
+    inner: I,
+    class: P,
+    backoff: B,
+}
+
+impl<I, P, B> ClassifiedBackoff<I, P, B> {
+    pub(crate) fn new(inner: I, class: P, backoff: B) -> Self {
+        Self {
+            inner,
+            class,
+            backoff,
+        }
+    }
+}
+
+impl<I: fmt::Debug, P, B: fmt::Debug> fmt::Debug for ClassifiedBackoff<I, P, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClassifiedBackoff")
+            .field("inner", &self.inner)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E, I, P, B> ErrorBackoff<E> for ClassifiedBackoff<I, P, B>
+where
+    I: ErrorBackoff<E>,
+    P: FnMut(&E) -> bool + Send + Sync + Unpin,
+    B: ErrorBackoff<E>,
+{
+    fn next_for(&mut self, err: &E) -> Option<Duration> {
+        if (self.class)(err) {
+            self.backoff.next_for(err)
+        } else {
+            self.inner.next_for(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BackoffBuilder;
+    use crate::ConstantBuilder;
+    use crate::ExponentialBuilder;
+
+    fn is_even(v: &u32) -> bool {
+        v % 2 == 0
+    }
+
+    #[test]
+    fn test_classified_backoff() {
+        let mut backoff = ClassifiedBackoff::new(
+            ExponentialBuilder::default().with_max_times(2).build(),
+            is_even,
+            ConstantBuilder::default()
+                .with_delay(Duration::from_secs(10))
+                .with_max_times(1)
+                .build(),
+        );
+
+        assert_eq!(Some(Duration::from_secs(1)), backoff.next_for(&1));
+        assert_eq!(Some(Duration::from_secs(10)), backoff.next_for(&2));
+        // Even errors are exhausted, odd errors still have their own attempts left.
+        assert_eq!(None, backoff.next_for(&4));
+        assert_eq!(Some(Duration::from_secs(2)), backoff.next_for(&3));
+        assert_eq!(None, backoff.next_for(&5));
+    }
+}
@@ -1,6 +1,9 @@
 use core::time::Duration;
 
+use crate::backoff::budget::Budget;
+use crate::backoff::jitter::Jitter;
 use crate::backoff::BackoffBuilder;
+use crate::backoff::ResettableBackoff;
 
 /// ConstantBuilder is used to create a [`ConstantBackoff`], providing a steady delay with a fixed number of retries.
 ///
@@ -35,6 +38,7 @@ use crate::backoff::BackoffBuilder;
 pub struct ConstantBuilder {
     delay: Duration,
     max_times: Option<usize>,
+    total_delay: Option<Duration>,
     jitter: bool,
     seed: Option<u64>,
 }
@@ -51,6 +55,7 @@ impl ConstantBuilder {
         Self {
             delay: Duration::from_secs(1),
             max_times: Some(3),
+            total_delay: None,
             jitter: false,
             seed: None,
         }
@@ -91,6 +96,15 @@ impl ConstantBuilder {
         self.max_times = None;
         self
     }
+
+    /// Set the total delay for the backoff.
+    ///
+    /// The backoff will stop yielding sleep durations once the cumulative sleep time
+    /// plus the next sleep duration would exceed `total_delay`.
+    pub const fn with_total_delay(mut self, total_delay: Option<Duration>) -> Self {
+        self.total_delay = total_delay;
+        self
+    }
 }
 
 impl BackoffBuilder for ConstantBuilder {
@@ -99,21 +113,8 @@ impl BackoffBuilder for ConstantBuilder {
     fn build(self) -> Self::Backoff {
         ConstantBackoff {
             delay: self.delay,
-            max_times: self.max_times,
-
-            attempts: 0,
-            jitter: self.jitter,
-            rng: if let Some(seed) = self.seed {
-                fastrand::Rng::with_seed(seed)
-            } else {
-                #[cfg(feature = "std")]
-                let rng = fastrand::Rng::new();
-
-                #[cfg(not(feature = "std"))]
-                let rng = fastrand::Rng::with_seed(super::RANDOM_SEED);
-
-                rng
-            },
+            budget: Budget::new(self.max_times, self.total_delay),
+            jitter: Jitter::new(self.jitter, self.seed),
         }
     }
 }
@@ -133,32 +134,32 @@ impl BackoffBuilder for &ConstantBuilder {
 #[derive(Debug)]
 pub struct ConstantBackoff {
     delay: Duration,
-    max_times: Option<usize>,
-
-    attempts: usize,
-    jitter: bool,
-    rng: fastrand::Rng,
+    budget: Budget,
+    jitter: Jitter,
 }
 
 impl Iterator for ConstantBackoff {
     type Item = Duration;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut delay = || match self.jitter {
-            true => self.delay + self.delay.mul_f32(self.rng.f32()),
-            false => self.delay,
-        };
-        match self.max_times {
-            None => Some(delay()),
-            Some(max_times) => {
-                if self.attempts >= max_times {
-                    None
-                } else {
-                    self.attempts += 1;
-                    Some(delay())
-                }
-            }
+        if !self.budget.try_attempt() {
+            return None;
+        }
+
+        let delay = self.jitter.apply(self.delay);
+
+        // Check if adding the current delay would exceed the total delay limit.
+        if !self.budget.try_spend(delay) {
+            return None;
         }
+
+        Some(delay)
+    }
+}
+
+impl ResettableBackoff for ConstantBackoff {
+    fn reset(&mut self) {
+        self.budget.reset();
     }
 }
 
@@ -224,6 +225,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_constant_total_delay() {
+        let mut it = ConstantBuilder::default()
+            .with_delay(Duration::from_secs(2))
+            .with_total_delay(Some(Duration::from_secs(5)))
+            .without_max_times()
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(2)), it.next());
+        assert_eq!(Some(Duration::from_secs(2)), it.next());
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_constant_total_delay_with_max_times() {
+        let mut it = ConstantBuilder::default()
+            .with_total_delay(Some(Duration::from_secs(10)))
+            .with_max_times(2)
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_constant_total_delay_with_jitter() {
+        let it = ConstantBuilder::default()
+            .with_jitter()
+            .with_total_delay(Some(Duration::from_secs(5)))
+            .without_max_times()
+            .build();
+
+        let cumulative_delay: Duration = it.sum();
+        assert!(cumulative_delay <= Duration::from_secs(5));
+        // Every delay is less than 2s, so at least 2 of them fit in 5s.
+        assert!(cumulative_delay >= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_constant_reset() {
+        let mut it = ConstantBuilder::default().with_max_times(2).build();
+
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(None, it.next());
+
+        it.reset();
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_constant_reset_total_delay() {
+        let mut it = ConstantBuilder::default()
+            .with_total_delay(Some(Duration::from_secs(1)))
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(None, it.next());
+
+        it.reset();
+        assert_eq!(Some(Duration::from_secs(1)), it.next());
+        assert_eq!(None, it.next());
+    }
+
     // allow assertions on constants because they are not optimized out by unit tests
     #[allow(clippy::assertions_on_constants)]
     #[test]
@@ -0,0 +1,382 @@
+use core::fmt;
+use core::time::Duration;
+
+use crate::backoff::budget::Budget;
+use crate::backoff::jitter::Jitter;
+use crate::backoff::BackoffBuilder;
+use crate::backoff::ResettableBackoff;
+
+/// Curve computes the delay before a retry from the attempt number.
+///
+/// `attempt` starts from `1` for the first retry. Any `Fn(u32) -> Duration` is a curve,
+/// and [`Polynomial`] provides delays in the form of `base * attempt^exponent`.
+pub trait Curve {
+    /// Returns the delay before the `attempt`-th retry.
+    fn delay(&self, attempt: u32) -> Duration;
+}
+
+impl<F: Fn(u32) -> Duration> Curve for F {
+    fn delay(&self, attempt: u32) -> Duration {
+        self(attempt)
+    }
+}
+
+/// Polynomial is a [`Curve`] with delays of `base * attempt^exponent`.
+///
+/// For example, an exponent of `2` gives quadratic delays of `base`, `4 * base`,
+/// `9 * base` and so on. The delay saturates at `Duration::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Polynomial {
+    base: Duration,
+    exponent: u32,
+}
+
+impl Polynomial {
+    /// Create a new `Polynomial` with delays of `base * attempt^exponent`.
+    pub const fn new(base: Duration, exponent: u32) -> Self {
+        Self { base, exponent }
+    }
+}
+
+impl Curve for Polynomial {
+    fn delay(&self, attempt: u32) -> Duration {
+        attempt
+            .checked_pow(self.exponent)
+            .and_then(|factor| self.base.checked_mul(factor))
+            .unwrap_or(Duration::MAX)
+    }
+}
+
+/// CurveBuilder is used to build a [`CurveBackoff`] which offers delays following a [`Curve`].
+///
+/// # Default
+///
+/// - jitter: false
+/// - max_delay: 60s
+/// - max_times: 3
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use backon::BackoffBuilder;
+/// use backon::CurveBuilder;
+///
+/// // Quadratic backoff: 100ms, 400ms, 900ms.
+/// let backoff = CurveBuilder::polynomial(Duration::from_millis(100), 2).build();
+/// assert_eq!(
+///     vec![
+///         Duration::from_millis(100),
+///         Duration::from_millis(400),
+///         Duration::from_millis(900),
+///     ],
+///     backoff.collect::<Vec<_>>()
+/// );
+///
+/// // Any function of the attempt number.
+/// let backoff = CurveBuilder::new(|attempt| Duration::from_secs(u64::from(attempt) * 10))
+///     .with_max_delay(Duration::from_secs(25))
+///     .build();
+/// assert_eq!(
+///     vec![
+///         Duration::from_secs(10),
+///         Duration::from_secs(20),
+///         Duration::from_secs(25),
+///     ],
+///     backoff.collect::<Vec<_>>()
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct CurveBuilder<C> {
+    curve: C,
+    jitter: bool,
+    seed: Option<u64>,
+    max_delay: Option<Duration>,
+    max_times: Option<usize>,
+    total_delay: Option<Duration>,
+}
+
+impl<C> fmt::Debug for CurveBuilder<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CurveBuilder")
+            .field("jitter", &self.jitter)
+            .field("seed", &self.seed)
+            .field("max_delay", &self.max_delay)
+            .field("max_times", &self.max_times)
+            .field("total_delay", &self.total_delay)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CurveBuilder<Polynomial> {
+    /// Create a new `CurveBuilder` with delays of `base * attempt^exponent`.
+    pub const fn polynomial(base: Duration, exponent: u32) -> Self {
+        Self::new(Polynomial::new(base, exponent))
+    }
+}
+
+impl<C> CurveBuilder<C> {
+    /// Create a new `CurveBuilder` with default values following `curve`.
+    pub const fn new(curve: C) -> Self {
+        Self {
+            curve,
+            jitter: false,
+            seed: None,
+            max_delay: Some(Duration::from_secs(60)),
+            max_times: Some(3),
+            total_delay: None,
+        }
+    }
+
+    /// Set the jitter for the backoff.
+    ///
+    /// When jitter is enabled, CurveBackoff will add a random jitter between `(0, current_delay)` to the delay.
+    pub const fn with_jitter(mut self) -> Self {
+        self.jitter = true;
+        self
+    }
+
+    /// Set the seed value for the jitter random number generator. If no seed is given, a random seed is used in std and default seed is used in no_std.
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the maximum delay for the backoff.
+    ///
+    /// Delays returned by the curve are capped at the maximum delay.
+    pub const fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Set no maximum delay for the backoff.
+    ///
+    /// The delay will follow the curve.
+    pub const fn without_max_delay(mut self) -> Self {
+        self.max_delay = None;
+        self
+    }
+
+    /// Set the maximum number of attempts for the current backoff.
+    ///
+    /// The backoff will stop if the maximum number of attempts is reached.
+    pub const fn with_max_times(mut self, max_times: usize) -> Self {
+        self.max_times = Some(max_times);
+        self
+    }
+
+    /// Set no maximum number of attempts for the current backoff.
+    ///
+    /// The backoff will not stop by itself.
+    ///
+    /// _The backoff could stop reaching `usize::MAX` attempts but this is **unrealistic**._
+    pub const fn without_max_times(mut self) -> Self {
+        self.max_times = None;
+        self
+    }
+
+    /// Set the total delay for the backoff.
+    ///
+    /// The backoff will stop yielding sleep durations once the cumulative sleep time
+    /// plus the next sleep duration would exceed `total_delay`.
+    pub const fn with_total_delay(mut self, total_delay: Option<Duration>) -> Self {
+        self.total_delay = total_delay;
+        self
+    }
+}
+
+impl<C: Curve + Send + Sync + Unpin> BackoffBuilder for CurveBuilder<C> {
+    type Backoff = CurveBackoff<C>;
+
+    fn build(self) -> Self::Backoff {
+        CurveBackoff {
+            curve: self.curve,
+            jitter: Jitter::new(self.jitter, self.seed),
+            max_delay: self.max_delay,
+            budget: Budget::new(self.max_times, self.total_delay),
+        }
+    }
+}
+
+impl<C: Curve + Clone + Send + Sync + Unpin> BackoffBuilder for &CurveBuilder<C> {
+    type Backoff = CurveBackoff<C>;
+
+    fn build(self) -> Self::Backoff {
+        self.clone().build()
+    }
+}
+
+/// CurveBackoff offers delays following a [`Curve`].
+///
+/// This backoff strategy is constructed by [`CurveBuilder`].
+#[doc(hidden)]
+pub struct CurveBackoff<C> {
+    curve: C,
+    jitter: Jitter,
+    max_delay: Option<Duration>,
+    budget: Budget,
+}
+
+impl<C> fmt::Debug for CurveBackoff<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CurveBackoff")
+            .field("jitter", &self.jitter.is_enabled())
+            .field("max_delay", &self.max_delay)
+            .field("budget", &self.budget)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: Curve> Iterator for CurveBackoff<C> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.budget.try_attempt() {
+            return None;
+        }
+
+        let attempt = u32::try_from(self.budget.attempts()).unwrap_or(u32::MAX);
+        let mut delay = self.curve.delay(attempt);
+        if let Some(max_delay) = self.max_delay {
+            delay = delay.min(max_delay);
+        }
+        let delay = self.jitter.apply(delay);
+
+        // Check if adding the current delay would exceed the total delay limit.
+        if !self.budget.try_spend(delay) {
+            return None;
+        }
+
+        Some(delay)
+    }
+}
+
+impl<C: Curve + Send + Sync + Unpin> ResettableBackoff for CurveBackoff<C> {
+    fn reset(&mut self) {
+        self.budget.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    const TEST_BUILDER: CurveBuilder<Polynomial> =
+        CurveBuilder::polynomial(Duration::from_secs(1), 3)
+            .with_jitter()
+            .with_max_delay(Duration::from_secs(30))
+            .with_max_times(5);
+
+    #[test]
+    fn test_curve_polynomial() {
+        let delays: Vec<_> = CurveBuilder::polynomial(Duration::from_millis(10), 2)
+            .with_max_times(4)
+            .build()
+            .collect();
+
+        assert_eq!(
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(40),
+                Duration::from_millis(90),
+                Duration::from_millis(160),
+            ],
+            delays
+        );
+    }
+
+    #[test]
+    fn test_curve_polynomial_saturates() {
+        let curve = Polynomial::new(Duration::from_secs(u64::MAX / 2), 2);
+
+        assert_eq!(Duration::from_secs(u64::MAX / 2), curve.delay(1));
+        assert_eq!(Duration::MAX, curve.delay(2));
+        assert_eq!(Duration::MAX, curve.delay(u32::MAX));
+    }
+
+    #[test]
+    fn test_curve_fn() {
+        let mut curve = CurveBuilder::new(|attempt| Duration::from_secs(u64::from(attempt)))
+            .with_max_times(2)
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), curve.next());
+        assert_eq!(Some(Duration::from_secs(2)), curve.next());
+        assert_eq!(None, curve.next());
+    }
+
+    #[test]
+    fn test_curve_max_delay() {
+        let mut curve = CurveBuilder::polynomial(Duration::from_secs(1), 2)
+            .with_max_delay(Duration::from_secs(5))
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), curve.next());
+        assert_eq!(Some(Duration::from_secs(4)), curve.next());
+        assert_eq!(Some(Duration::from_secs(5)), curve.next());
+        assert_eq!(None, curve.next());
+    }
+
+    #[test]
+    fn test_curve_total_delay() {
+        let mut curve = CurveBuilder::polynomial(Duration::from_secs(1), 1)
+            .with_total_delay(Some(Duration::from_secs(4)))
+            .without_max_times()
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), curve.next());
+        assert_eq!(Some(Duration::from_secs(2)), curve.next());
+        // 1 + 2 + 3 would exceed 4s.
+        assert_eq!(None, curve.next());
+    }
+
+    #[test]
+    fn test_curve_jitter() {
+        let mut curve = CurveBuilder::polynomial(Duration::from_secs(1), 2)
+            .with_jitter()
+            .build();
+
+        let v = curve.next().expect("value must valid");
+        assert!(v >= Duration::from_secs(1), "current: {v:?}");
+        assert!(v < Duration::from_secs(2), "current: {v:?}");
+
+        let v = curve.next().expect("value must valid");
+        assert!(v >= Duration::from_secs(4), "current: {v:?}");
+        assert!(v < Duration::from_secs(8), "current: {v:?}");
+    }
+
+    #[test]
+    fn test_curve_reset() {
+        let mut curve = CurveBuilder::polynomial(Duration::from_secs(1), 2).build();
+
+        assert_eq!(Some(Duration::from_secs(1)), curve.next());
+        assert_eq!(Some(Duration::from_secs(4)), curve.next());
+
+        curve.reset();
+        assert_eq!(Some(Duration::from_secs(1)), curve.next());
+    }
+
+    // allow assertions on constants because they are not optimized out by unit tests
+    #[allow(clippy::assertions_on_constants)]
+    #[test]
+    fn test_curve_const_builder() {
+        assert!(TEST_BUILDER.jitter);
+        assert_eq!(
+            TEST_BUILDER.curve,
+            Polynomial::new(Duration::from_secs(1), 3)
+        );
+        assert_eq!(TEST_BUILDER.max_delay, Some(Duration::from_secs(30)));
+        assert_eq!(TEST_BUILDER.max_times, Some(5));
+    }
+}
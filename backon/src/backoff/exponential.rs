@@ -1,6 +1,9 @@
 use core::time::Duration;
 
+use crate::backoff::budget::Budget;
+use crate::backoff::jitter::Jitter;
 use crate::backoff::BackoffBuilder;
+use crate::backoff::ResettableBackoff;
 
 /// ExponentialBuilder is used to construct an [`ExponentialBackoff`] that offers delays with exponential retries.
 ///
@@ -146,27 +149,13 @@ impl BackoffBuilder for ExponentialBuilder {
 
     fn build(self) -> Self::Backoff {
         ExponentialBackoff {
-            jitter: self.jitter,
-            rng: if let Some(seed) = self.seed {
-                fastrand::Rng::with_seed(seed)
-            } else {
-                #[cfg(feature = "std")]
-                let rng = fastrand::Rng::new();
-
-                #[cfg(not(feature = "std"))]
-                let rng = fastrand::Rng::with_seed(super::RANDOM_SEED);
-
-                rng
-            },
+            jitter: Jitter::new(self.jitter, self.seed),
             factor: self.factor,
             min_delay: self.min_delay,
             max_delay: self.max_delay,
-            max_times: self.max_times,
 
             current_delay: None,
-            attempts: 0,
-            cumulative_delay: Duration::ZERO,
-            total_delay: self.total_delay,
+            budget: Budget::new(self.max_times, self.total_delay),
         }
     }
 }
@@ -185,27 +174,22 @@ impl BackoffBuilder for &ExponentialBuilder {
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct ExponentialBackoff {
-    jitter: bool,
-    rng: fastrand::Rng,
+    jitter: Jitter,
     factor: f32,
     min_delay: Duration,
     max_delay: Option<Duration>,
-    max_times: Option<usize>,
-    total_delay: Option<Duration>,
+    budget: Budget,
 
     current_delay: Option<Duration>,
-    cumulative_delay: Duration,
-    attempts: usize,
 }
 
 impl Iterator for ExponentialBackoff {
     type Item = Duration;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.attempts >= self.max_times.unwrap_or(usize::MAX) {
+        if !self.budget.try_attempt() {
             return None;
         }
-        self.attempts += 1;
 
         let mut tmp_cur = match self.current_delay {
             None => {
@@ -229,30 +213,26 @@ impl Iterator for ExponentialBackoff {
         };
 
         let current_delay = tmp_cur;
-        // If jitter is enabled, add random jitter based on min delay.
-        if self.jitter {
-            tmp_cur = tmp_cur.saturating_add(tmp_cur.mul_f32(self.rng.f32()));
-        }
+        tmp_cur = self.jitter.apply(tmp_cur);
 
         // Check if adding the current delay would exceed the total delay limit.
-        let total_delay_check = self
-            .total_delay
-            .map_or(true, |total| self.cumulative_delay + tmp_cur <= total);
-
-        if !total_delay_check {
+        if !self.budget.try_spend(tmp_cur) {
             return None;
         }
 
-        if self.total_delay.is_some() {
-            self.cumulative_delay = self.cumulative_delay.saturating_add(tmp_cur);
-        }
-
         self.current_delay = Some(current_delay);
 
         Some(tmp_cur)
     }
 }
 
+impl ResettableBackoff for ExponentialBackoff {
+    fn reset(&mut self) {
+        self.current_delay = None;
+        self.budget.reset();
+    }
+}
+
 #[inline]
 pub(crate) fn saturating_mul(d: Duration, rhs: f32) -> Duration {
     Duration::try_from_secs_f32(rhs * d.as_secs_f32()).unwrap_or(Duration::MAX)
@@ -260,6 +240,9 @@ pub(crate) fn saturating_mul(d: Duration, rhs: f32) -> Duration {
 
 #[cfg(test)]
 mod tests {
+    extern crate alloc;
+
+    use alloc::vec::Vec;
     use core::time::Duration;
 
     #[cfg(target_arch = "wasm32")]
@@ -267,6 +250,7 @@ mod tests {
 
     use crate::BackoffBuilder;
     use crate::ExponentialBuilder;
+    use crate::ResettableBackoff;
 
     const TEST_BUILDER: ExponentialBuilder = ExponentialBuilder::new()
         .with_jitter()
@@ -448,6 +432,43 @@ mod tests {
         assert_eq!(None, exp.next());
     }
 
+    #[test]
+    fn test_exponential_reset() {
+        let mut exp = ExponentialBuilder::default()
+            .with_total_delay(Some(Duration::from_secs(7)))
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), exp.next());
+        assert_eq!(Some(Duration::from_secs(2)), exp.next());
+        assert_eq!(Some(Duration::from_secs(4)), exp.next());
+        assert_eq!(None, exp.next());
+
+        exp.reset();
+        assert_eq!(Some(Duration::from_secs(1)), exp.next());
+        assert_eq!(Some(Duration::from_secs(2)), exp.next());
+        assert_eq!(Some(Duration::from_secs(4)), exp.next());
+        assert_eq!(None, exp.next());
+    }
+
+    #[test]
+    fn test_exponential_reset_keeps_rng() {
+        let builder = ExponentialBuilder::default()
+            .with_jitter()
+            .with_jitter_seed(0x2fdb0020ffc7722b)
+            .with_max_times(6);
+
+        let expected: Vec<_> = builder.build().collect();
+
+        let mut exp = builder.build();
+        let first: Vec<_> = exp.by_ref().take(3).collect();
+        exp.reset();
+        let second: Vec<_> = exp.take(3).collect();
+
+        // The jitter keeps going from where it stopped instead of restarting from the seed.
+        assert_eq!(expected[..3], first[..]);
+        assert_ne!(first, second);
+    }
+
     // allow assertions on constants because they are not optimized out by unit tests
     #[allow(clippy::assertions_on_constants)]
     #[test]
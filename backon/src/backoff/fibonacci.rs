@@ -1,6 +1,9 @@
 use core::time::Duration;
 
+use crate::backoff::budget::Budget;
+use crate::backoff::jitter::Jitter;
 use crate::backoff::BackoffBuilder;
+use crate::backoff::ResettableBackoff;
 
 /// FibonacciBuilder is used to build a [`FibonacciBackoff`] which offers a delay with Fibonacci-based retries.
 ///
@@ -40,6 +43,7 @@ pub struct FibonacciBuilder {
     min_delay: Duration,
     max_delay: Option<Duration>,
     max_times: Option<usize>,
+    total_delay: Option<Duration>,
 }
 
 impl Default for FibonacciBuilder {
@@ -57,6 +61,7 @@ impl FibonacciBuilder {
             min_delay: Duration::from_secs(1),
             max_delay: Some(Duration::from_secs(60)),
             max_times: Some(3),
+            total_delay: None,
         }
     }
 
@@ -115,6 +120,15 @@ impl FibonacciBuilder {
         self.max_times = None;
         self
     }
+
+    /// Set the total delay for the backoff.
+    ///
+    /// The backoff will stop yielding sleep durations once the cumulative sleep time
+    /// plus the next sleep duration would exceed `total_delay`.
+    pub const fn with_total_delay(mut self, total_delay: Option<Duration>) -> Self {
+        self.total_delay = total_delay;
+        self
+    }
 }
 
 impl BackoffBuilder for FibonacciBuilder {
@@ -122,25 +136,13 @@ impl BackoffBuilder for FibonacciBuilder {
 
     fn build(self) -> Self::Backoff {
         FibonacciBackoff {
-            jitter: self.jitter,
-            rng: if let Some(seed) = self.seed {
-                fastrand::Rng::with_seed(seed)
-            } else {
-                #[cfg(feature = "std")]
-                let rng = fastrand::Rng::new();
-
-                #[cfg(not(feature = "std"))]
-                let rng = fastrand::Rng::with_seed(super::RANDOM_SEED);
-
-                rng
-            },
+            jitter: Jitter::new(self.jitter, self.seed),
             min_delay: self.min_delay,
             max_delay: self.max_delay,
-            max_times: self.max_times,
+            budget: Budget::new(self.max_times, self.total_delay),
 
             previous_delay: None,
             current_delay: None,
-            attempts: 0,
         }
     }
 }
@@ -159,38 +161,28 @@ impl BackoffBuilder for &FibonacciBuilder {
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct FibonacciBackoff {
-    jitter: bool,
-    rng: fastrand::Rng,
+    jitter: Jitter,
     min_delay: Duration,
     max_delay: Option<Duration>,
-    max_times: Option<usize>,
+    budget: Budget,
 
     previous_delay: Option<Duration>,
     current_delay: Option<Duration>,
-    attempts: usize,
 }
 
 impl Iterator for FibonacciBackoff {
     type Item = Duration;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.attempts >= self.max_times.unwrap_or(usize::MAX) {
+        if !self.budget.try_attempt() {
             return None;
         }
-        self.attempts += 1;
 
-        match self.current_delay {
+        let next = match self.current_delay {
             None => {
                 // If current_delay is None, it's must be the first time to retry.
-                let mut next = self.min_delay;
-                self.current_delay = Some(next);
-
-                // If jitter is enabled, add random jitter based on min delay.
-                if self.jitter {
-                    next += next.mul_f32(self.rng.f32());
-                }
-
-                Some(next)
+                self.current_delay = Some(self.min_delay);
+                self.jitter.apply(self.min_delay)
             }
             Some(cur) => {
                 let mut next = cur;
@@ -204,14 +196,25 @@ impl Iterator for FibonacciBackoff {
                     self.previous_delay = Some(cur);
                 }
 
-                // If jitter is enabled, add random jitter based on min delay.
-                if self.jitter {
-                    next += self.min_delay.mul_f32(self.rng.f32());
-                }
-
-                Some(next)
+                // The jitter is based on min delay.
+                next.saturating_add(self.jitter.sample(self.min_delay))
             }
+        };
+
+        // Check if adding the current delay would exceed the total delay limit.
+        if !self.budget.try_spend(next) {
+            return None;
         }
+
+        Some(next)
+    }
+}
+
+impl ResettableBackoff for FibonacciBackoff {
+    fn reset(&mut self) {
+        self.previous_delay = None;
+        self.current_delay = None;
+        self.budget.reset();
     }
 }
 
@@ -333,6 +336,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fibonacci_total_delay() {
+        let mut fib = FibonacciBuilder::default()
+            .with_total_delay(Some(Duration::from_secs(6)))
+            .without_max_times()
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(2)), fib.next());
+        // 1 + 1 + 2 + 3 would exceed 6s.
+        assert_eq!(None, fib.next());
+    }
+
+    #[test]
+    fn test_fibonacci_total_delay_with_jitter() {
+        let fib = FibonacciBuilder::default()
+            .with_jitter()
+            .with_total_delay(Some(Duration::from_secs(10)))
+            .without_max_times()
+            .build();
+
+        let cumulative_delay: Duration = fib.sum();
+        assert!(cumulative_delay <= Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_fibonacci_reset() {
+        let mut fib = FibonacciBuilder::default().build();
+
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(2)), fib.next());
+        assert_eq!(None, fib.next());
+
+        fib.reset();
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(2)), fib.next());
+        assert_eq!(None, fib.next());
+    }
+
+    #[test]
+    fn test_fibonacci_reset_total_delay() {
+        let mut fib = FibonacciBuilder::default()
+            .with_total_delay(Some(Duration::from_secs(2)))
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(None, fib.next());
+
+        fib.reset();
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(Some(Duration::from_secs(1)), fib.next());
+        assert_eq!(None, fib.next());
+    }
+
     // allow assertions on constants because they are not optimized out by unit tests
     #[allow(clippy::assertions_on_constants)]
     #[test]
@@ -0,0 +1,75 @@
+use core::time::Duration;
+
+use crate::backoff::exponential::saturating_mul;
+
+/// Jitter adds a random delay on top of the delays of a backoff.
+///
+/// It's shared by all builtin backoffs so `with_jitter` and `with_jitter_seed` behave the
+/// same regardless of how the delays are computed.
+#[derive(Debug)]
+pub(crate) struct Jitter {
+    enabled: bool,
+    rng: JitterRng,
+}
+
+#[derive(Debug)]
+enum JitterRng {
+    /// Draw factors from a random number generator.
+    Random(fastrand::Rng),
+    /// Always use the same factor, used to compute the bounds of a backoff.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    Fixed(f32),
+}
+
+impl Jitter {
+    /// Build a jitter seeded from `seed`, or a random seed if not set.
+    ///
+    /// While previewed by [`Schedule`](crate::Schedule), the jitter factor is fixed instead.
+    pub(crate) fn new(enabled: bool, seed: Option<u64>) -> Self {
+        #[cfg(feature = "std")]
+        if let Some(factor) = crate::backoff::schedule::preview_jitter(enabled) {
+            return Self {
+                enabled,
+                rng: JitterRng::Fixed(factor),
+            };
+        }
+
+        let rng = if let Some(seed) = seed {
+            fastrand::Rng::with_seed(seed)
+        } else {
+            #[cfg(feature = "std")]
+            let rng = fastrand::Rng::new();
+
+            #[cfg(not(feature = "std"))]
+            let rng = fastrand::Rng::with_seed(super::RANDOM_SEED);
+
+            rng
+        };
+        Self {
+            enabled,
+            rng: JitterRng::Random(rng),
+        }
+    }
+
+    /// Returns `true` if jitter is enabled.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Add a random jitter between `[0, delay)` to `delay` if jitter is enabled.
+    pub(crate) fn apply(&mut self, delay: Duration) -> Duration {
+        delay.saturating_add(self.sample(delay))
+    }
+
+    /// Returns a random jitter between `[0, base)`, or zero if jitter is disabled.
+    pub(crate) fn sample(&mut self, base: Duration) -> Duration {
+        if !self.enabled {
+            return Duration::ZERO;
+        }
+        let factor = match &mut self.rng {
+            JitterRng::Random(rng) => rng.f32(),
+            JitterRng::Fixed(factor) => *factor,
+        };
+        saturating_mul(base, factor)
+    }
+}
@@ -1,6 +1,9 @@
 mod api;
 pub use api::*;
 
+mod budget;
+mod jitter;
+
 mod constant;
 pub use constant::ConstantBackoff;
 pub use constant::ConstantBuilder;
@@ -9,10 +12,38 @@ mod fibonacci;
 pub use fibonacci::FibonacciBackoff;
 pub use fibonacci::FibonacciBuilder;
 
+mod curve;
+pub use curve::Curve;
+pub use curve::CurveBackoff;
+pub use curve::CurveBuilder;
+pub use curve::Polynomial;
+
+mod sequence;
+pub use sequence::SequenceBackoff;
+pub use sequence::SequenceBuilder;
+
+mod classified;
+pub use classified::ClassifiedBackoff;
+
 mod exponential;
 pub use exponential::ExponentialBackoff;
 pub use exponential::ExponentialBuilder;
 
+#[cfg(feature = "std")]
+mod schedule;
+#[cfg(feature = "std")]
+pub use schedule::Schedule;
+
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub use shared::SharedBackoff;
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod auto_reset;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub use auto_reset::AutoResetBackoff;
+
 // Random seed value for no_std (the value is "backon" in hex)
 #[cfg(not(feature = "std"))]
 const RANDOM_SEED: u64 = 0x6261636b6f6e;
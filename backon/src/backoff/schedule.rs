@@ -0,0 +1,412 @@
+use core::cell::Cell;
+use core::time::Duration;
+use std::vec::Vec;
+
+use crate::backoff::BackoffBuilder;
+
+/// Schedule is a report of the delays a backoff yields, computed without sleeping.
+///
+/// It answers questions like "how long can this retry block for?" by running the
+/// backoff built from a [`BackoffBuilder`] to the end. Jitter is accounted for by
+/// running the backoff twice: once with the smallest jitter factor and once with the
+/// largest, which gives the bounds of every delay.
+///
+/// Jitter is only known for the backoffs provided by backon, including the ones built
+/// by custom builders. Other backoffs are run once and their delays are reported as is.
+///
+/// Backoffs that never stop are cut after [`Schedule::LIMIT`] retries, see
+/// [`Schedule::is_truncated`].
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use backon::ExponentialBuilder;
+/// use backon::Schedule;
+///
+/// let schedule = Schedule::new(ExponentialBuilder::default().with_max_times(8));
+///
+/// assert_eq!(9, schedule.attempts());
+/// assert_eq!(Duration::from_secs(1), schedule.delays()[0]);
+/// // 1 + 2 + 4 + 8 + 16 + 32 + 60 + 60, the delay is capped at 60s.
+/// assert_eq!(Duration::from_secs(183), schedule.total_delay());
+/// assert_eq!(schedule.total_delay(), schedule.max_total_delay());
+///
+/// // With jitter, every delay can be up to twice as long.
+/// let schedule = Schedule::new(
+///     ExponentialBuilder::default()
+///         .with_max_times(8)
+///         .with_jitter(),
+/// );
+/// assert_eq!(Duration::from_secs(183), schedule.total_delay());
+/// assert_eq!(Duration::from_secs(366), schedule.max_total_delay());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    delays: Vec<Duration>,
+    max_delays: Vec<Duration>,
+    max_total_delay: Duration,
+    truncated: bool,
+}
+
+impl Schedule {
+    /// The maximum number of retries collected from a backoff.
+    pub const LIMIT: usize = 1024;
+
+    /// Compute the schedule of the backoff built by `builder`.
+    ///
+    /// The builder must be [`Clone`] because it's used to build the backoff once per
+    /// bound. All builders provided by backon and references to them are [`Copy`].
+    pub fn new<B: BackoffBuilder + Clone>(builder: B) -> Self {
+        let min = collect(builder.clone(), 0.0, false);
+        if !min.jittered {
+            return Schedule {
+                max_total_delay: sum(&min.delays),
+                max_delays: min.delays.clone(),
+                delays: min.delays,
+                truncated: min.truncated,
+            };
+        }
+
+        let max = collect(builder.clone(), 1.0, false);
+        // Jitter factors between the bounds can fit more retries under a total delay
+        // limit than either of them, so bound them by the largest delays without it.
+        let max_total_delay = match min.total_delay_limit {
+            Some(limit) => limit.min(sum(&collect(builder, 1.0, true).delays)),
+            None => sum(&max.delays),
+        };
+
+        Schedule {
+            delays: min.delays,
+            max_delays: max.delays,
+            max_total_delay,
+            truncated: min.truncated || max.truncated,
+        }
+    }
+
+    /// Returns the delay before every retry without jitter.
+    ///
+    /// With jitter, this is the lower bound of every delay.
+    pub fn delays(&self) -> &[Duration] {
+        &self.delays
+    }
+
+    /// Returns the upper bound of the delay before every retry with jitter.
+    ///
+    /// This is the same as [`Schedule::delays`] if jitter is not enabled.
+    pub fn max_delays(&self) -> &[Duration] {
+        &self.max_delays
+    }
+
+    /// Returns the cumulative delay after every retry without jitter.
+    pub fn cumulative_delays(&self) -> Vec<Duration> {
+        self.delays
+            .iter()
+            .scan(Duration::ZERO, |total, delay| {
+                *total = total.saturating_add(*delay);
+                Some(*total)
+            })
+            .collect()
+    }
+
+    /// Returns the total delay without jitter.
+    pub fn total_delay(&self) -> Duration {
+        sum(&self.delays)
+    }
+
+    /// Returns the worst-case total delay with jitter.
+    ///
+    /// With jitter and a total delay limit, jitter factors between the smallest and the
+    /// largest can fit more retries under the limit than either of them. This is then the
+    /// smaller of the limit and the sum of the largest delays without the limit.
+    pub fn max_total_delay(&self) -> Duration {
+        self.max_total_delay
+    }
+
+    /// Returns the maximum number of retries.
+    ///
+    /// Larger delays never allow more retries, so this is the number of retries with
+    /// the smallest jitter factor.
+    pub fn retries(&self) -> usize {
+        self.delays.len().max(self.max_delays.len())
+    }
+
+    /// Returns the maximum number of attempts, including the first one.
+    pub fn attempts(&self) -> usize {
+        self.retries() + 1
+    }
+
+    /// Returns `true` if the backoff didn't stop within [`Schedule::LIMIT`] retries.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// Run is the result of running a backoff under [`Preview`].
+struct Run {
+    delays: Vec<Duration>,
+    truncated: bool,
+    jittered: bool,
+    total_delay_limit: Option<Duration>,
+}
+
+/// Collect the delays of the backoff built with a fixed jitter `factor`, ignoring the
+/// total delay limits if `unlimited`.
+fn collect<B: BackoffBuilder>(builder: B, factor: f32, unlimited: bool) -> Run {
+    let previewing = Previewing::enter(factor, unlimited);
+    let mut backoff = builder.build();
+    let delays: Vec<_> = backoff.by_ref().take(Schedule::LIMIT).collect();
+    let truncated = delays.len() == Schedule::LIMIT && backoff.next().is_some();
+    let preview = previewing.finish();
+
+    Run {
+        delays,
+        truncated,
+        jittered: preview.jittered,
+        total_delay_limit: preview.total_delay_limit,
+    }
+}
+
+/// Preview is what [`Schedule`] learns from the builtin backoffs built on this thread.
+///
+/// Builtin backoffs look it up when they are built, which works through any builder
+/// or backoff wrapping them without extending the public traits.
+#[derive(Clone, Copy, Debug, Default)]
+struct Preview {
+    /// The jitter factor used instead of a random one.
+    factor: f32,
+    /// Whether the total delay limits are ignored.
+    unlimited: bool,
+    /// Whether a backoff with jitter has been built.
+    jittered: bool,
+    /// The smallest total delay limit of the backoffs built.
+    total_delay_limit: Option<Duration>,
+}
+
+std::thread_local! {
+    static PREVIEW: Cell<Option<Preview>> = const { Cell::new(None) };
+}
+
+/// Previewing restores the previous [`Preview`] when dropped, even if the backoff panics.
+struct Previewing {
+    previous: Option<Preview>,
+}
+
+impl Previewing {
+    fn enter(factor: f32, unlimited: bool) -> Self {
+        let preview = Preview {
+            factor,
+            unlimited,
+            ..Preview::default()
+        };
+        Previewing {
+            previous: PREVIEW.with(|cell| cell.replace(Some(preview))),
+        }
+    }
+
+    fn finish(self) -> Preview {
+        PREVIEW.with(|cell| cell.get()).unwrap_or_default()
+    }
+}
+
+impl Drop for Previewing {
+    fn drop(&mut self) {
+        PREVIEW.with(|cell| cell.set(self.previous));
+    }
+}
+
+/// Update the current [`Preview`], returns `None` if not previewing.
+fn update_preview<T>(f: impl FnOnce(&mut Preview) -> T) -> Option<T> {
+    PREVIEW.with(|cell| {
+        let mut preview = cell.get()?;
+        let output = f(&mut preview);
+        cell.set(Some(preview));
+        Some(output)
+    })
+}
+
+/// Returns the jitter factor to use instead of a random one if previewing.
+pub(crate) fn preview_jitter(enabled: bool) -> Option<f32> {
+    update_preview(|preview| {
+        preview.jittered |= enabled;
+        preview.factor
+    })
+}
+
+/// Record the total delay limit of a backoff if previewing, returns the limit to use.
+pub(crate) fn preview_total_delay(total_delay: Option<Duration>) -> Option<Duration> {
+    update_preview(|preview| {
+        preview.total_delay_limit = match (preview.total_delay_limit, total_delay) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if preview.unlimited {
+            None
+        } else {
+            total_delay
+        }
+    })
+    .unwrap_or(total_delay)
+}
+
+fn sum(delays: &[Duration]) -> Duration {
+    delays
+        .iter()
+        .fold(Duration::ZERO, |total, delay| total.saturating_add(*delay))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::ConstantBackoff;
+    use crate::ConstantBuilder;
+    use crate::ExponentialBuilder;
+    use crate::FibonacciBuilder;
+    use crate::SequenceBuilder;
+
+    #[test]
+    fn test_schedule_exponential() {
+        let schedule = Schedule::new(ExponentialBuilder::default());
+
+        let delays = vec![
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(4),
+        ];
+        assert_eq!(delays, schedule.delays());
+        assert_eq!(delays, schedule.max_delays());
+        assert_eq!(
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(3),
+                Duration::from_secs(7),
+            ],
+            schedule.cumulative_delays()
+        );
+        assert_eq!(Duration::from_secs(7), schedule.total_delay());
+        assert_eq!(Duration::from_secs(7), schedule.max_total_delay());
+        assert_eq!(3, schedule.retries());
+        assert_eq!(4, schedule.attempts());
+        assert!(!schedule.is_truncated());
+    }
+
+    #[test]
+    fn test_schedule_jitter() {
+        let schedule = Schedule::new(
+            ConstantBuilder::default()
+                .with_delay(Duration::from_secs(2))
+                .with_max_times(2)
+                .with_jitter(),
+        );
+
+        assert_eq!(
+            vec![Duration::from_secs(2), Duration::from_secs(2)],
+            schedule.delays()
+        );
+        assert_eq!(
+            vec![Duration::from_secs(4), Duration::from_secs(4)],
+            schedule.max_delays()
+        );
+        assert_eq!(Duration::from_secs(4), schedule.total_delay());
+        assert_eq!(Duration::from_secs(8), schedule.max_total_delay());
+        assert_eq!(3, schedule.attempts());
+    }
+
+    #[test]
+    fn test_schedule_jitter_with_total_delay() {
+        let schedule = Schedule::new(
+            ExponentialBuilder::default()
+                .with_jitter()
+                .with_total_delay(Some(Duration::from_secs(10))),
+        );
+
+        // The larger delays reach the total delay limit earlier.
+        assert_eq!(3, schedule.delays().len());
+        assert_eq!(2, schedule.max_delays().len());
+        assert_eq!(4, schedule.attempts());
+        // A factor of 0.4 yields 1.4s, 2.8s and 5.6s, which is more than either bound.
+        assert_eq!(Duration::from_secs(10), schedule.max_total_delay());
+    }
+
+    #[test]
+    fn test_schedule_jitter_with_total_delay_and_max_times() {
+        let schedule = Schedule::new(
+            ExponentialBuilder::default()
+                .with_max_times(1)
+                .with_jitter()
+                .with_total_delay(Some(Duration::from_secs(100))),
+        );
+
+        // The only retry can't wait for longer than 2s, far below the limit.
+        assert_eq!(Duration::from_secs(1), schedule.total_delay());
+        assert_eq!(Duration::from_secs(2), schedule.max_total_delay());
+    }
+
+    #[test]
+    fn test_schedule_total_delay_without_jitter() {
+        let schedule = Schedule::new(
+            ExponentialBuilder::default().with_total_delay(Some(Duration::from_secs(10))),
+        );
+
+        assert_eq!(Duration::from_secs(7), schedule.total_delay());
+        assert_eq!(Duration::from_secs(7), schedule.max_total_delay());
+    }
+
+    #[test]
+    fn test_schedule_truncated() {
+        let schedule = Schedule::new(FibonacciBuilder::default().without_max_times());
+
+        assert_eq!(Schedule::LIMIT, schedule.retries());
+        assert!(schedule.is_truncated());
+    }
+
+    #[test]
+    fn test_schedule_sequence() {
+        let delays = [Duration::from_millis(1), Duration::from_millis(2)];
+        let schedule = Schedule::new(SequenceBuilder::new(delays));
+
+        assert_eq!(delays, schedule.delays());
+        assert_eq!(delays, schedule.max_delays());
+        assert_eq!(3, schedule.attempts());
+        assert!(!schedule.is_truncated());
+    }
+
+    #[test]
+    fn test_schedule_backoff() {
+        let delays = [Duration::from_millis(1), Duration::from_millis(2)];
+        let schedule = Schedule::new(delays.into_iter());
+
+        assert_eq!(delays, schedule.delays());
+        assert_eq!(delays, schedule.max_delays());
+        assert_eq!(3, schedule.attempts());
+        assert!(!schedule.is_truncated());
+    }
+
+    #[test]
+    fn test_schedule_custom_builder() {
+        #[derive(Clone)]
+        struct Custom;
+
+        impl BackoffBuilder for Custom {
+            type Backoff = ConstantBackoff;
+
+            fn build(self) -> Self::Backoff {
+                ConstantBuilder::default()
+                    .with_max_times(1)
+                    .with_jitter()
+                    .build()
+            }
+        }
+
+        // The jitter of builtin backoffs is known through custom builders too.
+        let schedule = Schedule::new(Custom);
+        assert_eq!(vec![Duration::from_secs(1)], schedule.delays());
+        assert_eq!(vec![Duration::from_secs(2)], schedule.max_delays());
+    }
+}
@@ -0,0 +1,300 @@
+use core::time::Duration;
+
+use crate::backoff::budget::Budget;
+use crate::backoff::jitter::Jitter;
+use crate::backoff::BackoffBuilder;
+use crate::backoff::ResettableBackoff;
+
+/// SequenceBuilder is used to build a [`SequenceBackoff`] which replays a list of delays.
+///
+/// The delays can be any `AsRef<[Duration]>`, like an array, a slice or a `Vec`.
+///
+/// # Default
+///
+/// - jitter: false
+/// - repeat_last: false
+/// - max_times: unlimited, the backoff stops at the end of the delays
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use backon::BackoffBuilder;
+/// use backon::SequenceBuilder;
+///
+/// const BACKOFF: SequenceBuilder<[Duration; 3]> = SequenceBuilder::new([
+///     Duration::from_millis(100),
+///     Duration::from_millis(500),
+///     Duration::from_secs(2),
+/// ])
+/// .with_repeat_last()
+/// .with_max_times(5);
+///
+/// assert_eq!(
+///     vec![
+///         Duration::from_millis(100),
+///         Duration::from_millis(500),
+///         Duration::from_secs(2),
+///         Duration::from_secs(2),
+///         Duration::from_secs(2),
+///     ],
+///     BACKOFF.build().collect::<Vec<_>>()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SequenceBuilder<D> {
+    delays: D,
+    repeat_last: bool,
+    jitter: bool,
+    seed: Option<u64>,
+    max_times: Option<usize>,
+    total_delay: Option<Duration>,
+}
+
+impl<D> SequenceBuilder<D> {
+    /// Create a new `SequenceBuilder` that replays `delays`.
+    pub const fn new(delays: D) -> Self {
+        Self {
+            delays,
+            repeat_last: false,
+            jitter: false,
+            seed: None,
+            max_times: None,
+            total_delay: None,
+        }
+    }
+
+    /// Keep repeating the last delay once all delays have been used.
+    ///
+    /// The backoff will not stop by itself unless limited by `max_times` or `total_delay`.
+    pub const fn with_repeat_last(mut self) -> Self {
+        self.repeat_last = true;
+        self
+    }
+
+    /// Set the jitter for the backoff.
+    ///
+    /// When jitter is enabled, SequenceBackoff will add a random jitter between `(0, current_delay)` to the delay.
+    pub const fn with_jitter(mut self) -> Self {
+        self.jitter = true;
+        self
+    }
+
+    /// Set the seed value for the jitter random number generator. If no seed is given, a random seed is used in std and default seed is used in no_std.
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the maximum number of attempts for the current backoff.
+    ///
+    /// The backoff will stop if the maximum number of attempts is reached.
+    pub const fn with_max_times(mut self, max_times: usize) -> Self {
+        self.max_times = Some(max_times);
+        self
+    }
+
+    /// Set no maximum number of attempts for the current backoff.
+    ///
+    /// The backoff will stop at the end of the delays, or never if the last delay is repeated.
+    pub const fn without_max_times(mut self) -> Self {
+        self.max_times = None;
+        self
+    }
+
+    /// Set the total delay for the backoff.
+    ///
+    /// The backoff will stop yielding sleep durations once the cumulative sleep time
+    /// plus the next sleep duration would exceed `total_delay`.
+    pub const fn with_total_delay(mut self, total_delay: Option<Duration>) -> Self {
+        self.total_delay = total_delay;
+        self
+    }
+}
+
+impl<D: AsRef<[Duration]> + Send + Sync + Unpin> BackoffBuilder for SequenceBuilder<D> {
+    type Backoff = SequenceBackoff<D>;
+
+    fn build(self) -> Self::Backoff {
+        SequenceBackoff {
+            delays: self.delays,
+            repeat_last: self.repeat_last,
+            jitter: Jitter::new(self.jitter, self.seed),
+            budget: Budget::new(self.max_times, self.total_delay),
+        }
+    }
+}
+
+impl<D: AsRef<[Duration]> + Clone + Send + Sync + Unpin> BackoffBuilder for &SequenceBuilder<D> {
+    type Backoff = SequenceBackoff<D>;
+
+    fn build(self) -> Self::Backoff {
+        self.clone().build()
+    }
+}
+
+/// SequenceBackoff offers delays replayed from a list.
+///
+/// This backoff strategy is constructed by [`SequenceBuilder`].
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct SequenceBackoff<D> {
+    delays: D,
+    repeat_last: bool,
+    jitter: Jitter,
+    budget: Budget,
+}
+
+impl<D: AsRef<[Duration]>> Iterator for SequenceBackoff<D> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.budget.try_attempt() {
+            return None;
+        }
+
+        let delays = self.delays.as_ref();
+        let delay = match delays.get(self.budget.attempts() - 1) {
+            Some(delay) => *delay,
+            None if self.repeat_last => *delays.last()?,
+            None => return None,
+        };
+        let delay = self.jitter.apply(delay);
+
+        // Check if adding the current delay would exceed the total delay limit.
+        if !self.budget.try_spend(delay) {
+            return None;
+        }
+
+        Some(delay)
+    }
+}
+
+impl<D: AsRef<[Duration]> + Send + Sync + Unpin> ResettableBackoff for SequenceBackoff<D> {
+    fn reset(&mut self) {
+        self.budget.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    const DELAYS: [Duration; 3] = [
+        Duration::from_secs(1),
+        Duration::from_secs(2),
+        Duration::from_secs(5),
+    ];
+
+    const TEST_BUILDER: SequenceBuilder<[Duration; 3]> = SequenceBuilder::new(DELAYS)
+        .with_repeat_last()
+        .with_jitter()
+        .with_max_times(5);
+
+    #[test]
+    fn test_sequence() {
+        let delays: Vec<_> = SequenceBuilder::new(DELAYS).build().collect();
+
+        assert_eq!(DELAYS.to_vec(), delays);
+    }
+
+    #[test]
+    fn test_sequence_repeat_last() {
+        let mut seq = SequenceBuilder::new(DELAYS).with_repeat_last().build();
+
+        assert_eq!(Some(Duration::from_secs(1)), seq.next());
+        assert_eq!(Some(Duration::from_secs(2)), seq.next());
+        for _ in 0..10_000 {
+            assert_eq!(Some(Duration::from_secs(5)), seq.next());
+        }
+    }
+
+    #[test]
+    fn test_sequence_max_times() {
+        let mut seq = SequenceBuilder::new(DELAYS).with_max_times(2).build();
+
+        assert_eq!(Some(Duration::from_secs(1)), seq.next());
+        assert_eq!(Some(Duration::from_secs(2)), seq.next());
+        assert_eq!(None, seq.next());
+    }
+
+    #[test]
+    fn test_sequence_total_delay() {
+        let mut seq = SequenceBuilder::new(DELAYS)
+            .with_repeat_last()
+            .with_total_delay(Some(Duration::from_secs(10)))
+            .build();
+
+        assert_eq!(Some(Duration::from_secs(1)), seq.next());
+        assert_eq!(Some(Duration::from_secs(2)), seq.next());
+        assert_eq!(Some(Duration::from_secs(5)), seq.next());
+        // 1 + 2 + 5 + 5 would exceed 10s.
+        assert_eq!(None, seq.next());
+    }
+
+    #[test]
+    fn test_sequence_jitter() {
+        let mut seq = SequenceBuilder::new(DELAYS).with_jitter().build();
+
+        for expected in DELAYS {
+            let v = seq.next().expect("value must valid");
+            assert!(v >= expected, "current: {v:?}");
+            assert!(v < expected * 2, "current: {v:?}");
+        }
+        assert_eq!(None, seq.next());
+    }
+
+    #[test]
+    fn test_sequence_empty() {
+        let mut seq = SequenceBuilder::new([Duration::ZERO; 0])
+            .with_repeat_last()
+            .build();
+
+        assert_eq!(None, seq.next());
+    }
+
+    #[test]
+    fn test_sequence_reuse_builder() {
+        let builder = SequenceBuilder::new(vec![Duration::from_millis(100)]);
+
+        assert_eq!(
+            vec![Duration::from_millis(100)],
+            (&builder).build().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Duration::from_millis(100)],
+            builder.build().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_sequence_reset() {
+        let mut seq = SequenceBuilder::new(DELAYS).build();
+
+        assert_eq!(Some(Duration::from_secs(1)), seq.next());
+        assert_eq!(Some(Duration::from_secs(2)), seq.next());
+
+        seq.reset();
+        assert_eq!(Some(Duration::from_secs(1)), seq.next());
+    }
+
+    // allow assertions on constants because they are not optimized out by unit tests
+    #[allow(clippy::assertions_on_constants)]
+    #[test]
+    fn test_sequence_const_builder() {
+        assert_eq!(TEST_BUILDER.delays, DELAYS);
+        assert!(TEST_BUILDER.repeat_last);
+        assert!(TEST_BUILDER.jitter);
+        assert_eq!(TEST_BUILDER.max_times, Some(5));
+    }
+}
@@ -0,0 +1,173 @@
+use core::time::Duration;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use crate::backoff::Backoff;
+use crate::backoff::BackoffBuilder;
+use crate::backoff::ResettableBackoff;
+
+/// SharedBackoff is a handle to a single backoff shared by many retries.
+///
+/// All clones of a `SharedBackoff` draw their delays from the same backoff. When
+/// many tasks retry against the same dependency, every failure moves the shared
+/// backoff forward, so the tasks collectively slow down instead of each running
+/// its own backoff in lock-step. Once the shared backoff is exhausted, all retries
+/// using it give up.
+///
+/// Call [`reset`](ResettableBackoff::reset) after the dependency recovers, or wrap a
+/// backoff in [`AutoResetBackoff`](crate::AutoResetBackoff) to reset it automatically.
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use backon::ExponentialBuilder;
+/// use backon::SharedBackoff;
+///
+/// let backoff = SharedBackoff::new(ExponentialBuilder::default());
+///
+/// // Two tasks failing against the same host.
+/// let mut a = backoff.clone();
+/// let mut b = backoff.clone();
+///
+/// assert_eq!(Some(Duration::from_secs(1)), a.next());
+/// assert_eq!(Some(Duration::from_secs(2)), b.next());
+/// assert_eq!(Some(Duration::from_secs(4)), a.next());
+/// assert_eq!(None, b.next());
+/// ```
+///
+/// Every `Retry` takes a clone of the handle:
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use backon::ExponentialBuilder;
+/// use backon::Retryable;
+/// use backon::SharedBackoff;
+///
+/// async fn connect() -> Result<()> {
+///     Ok(())
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<()> {
+///     let backoff = SharedBackoff::new(ExponentialBuilder::default().with_max_times(10));
+///
+///     let tasks: Vec<_> = (0..8)
+///         .map(|_| tokio::spawn(connect.retry(backoff.clone())))
+///         .collect();
+///     for task in tasks {
+///         task.await??;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SharedBackoff<B: Backoff> {
+    inner: Arc<Mutex<B>>,
+}
+
+impl<B: Backoff> SharedBackoff<B> {
+    /// Create a new `SharedBackoff` from the backoff built by `builder`.
+    pub fn new(builder: impl BackoffBuilder<Backoff = B>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(builder.build())),
+        }
+    }
+}
+
+impl<B: Backoff> Clone for SharedBackoff<B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<B: Backoff> Iterator for SharedBackoff<B> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // A backoff can't be left in an invalid state, so it's fine to ignore poisoning.
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .next()
+    }
+}
+
+impl<B: ResettableBackoff> ResettableBackoff for SharedBackoff<B> {
+    /// Reset the shared backoff for all clones.
+    fn reset(&mut self) {
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .reset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::ExponentialBuilder;
+
+    #[test]
+    fn test_shared_backoff_reset() {
+        let backoff = SharedBackoff::new(ExponentialBuilder::default().with_max_times(2));
+        let mut a = backoff.clone();
+        let mut b = backoff.clone();
+
+        assert_eq!(Some(Duration::from_secs(1)), a.next());
+        assert_eq!(Some(Duration::from_secs(2)), b.next());
+        assert_eq!(None, a.next());
+
+        b.reset();
+        assert_eq!(Some(Duration::from_secs(1)), a.next());
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use core::future::ready;
+    use core::time::Duration;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use tokio::test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::ConstantBuilder;
+    use crate::Retryable;
+
+    #[test]
+    async fn test_shared_backoff_retry() {
+        let backoff = SharedBackoff::new(
+            ConstantBuilder::default()
+                .with_delay(Duration::from_millis(1))
+                .with_max_times(3),
+        );
+        let attempts = spin::Mutex::new(0);
+        let f = || {
+            *attempts.lock() += 1;
+            ready(Err::<(), _>("error"))
+        };
+
+        let (a, b) = tokio::join!(
+            f.retry(backoff.clone()).sleep(|_| ready(())),
+            f.retry(backoff.clone()).sleep(|_| ready(())),
+        );
+
+        assert_eq!(Err("error"), a);
+        assert_eq!(Err("error"), b);
+        // Both first attempts plus 3 retries drawn from the shared backoff.
+        assert_eq!(5, *attempts.lock());
+    }
+}
@@ -1,10 +1,19 @@
+#[cfg(feature = "tracing")]
+use core::fmt::Debug;
+#[cfg(feature = "tracing")]
+use core::fmt::Display;
 use core::time::Duration;
 
 use crate::backoff::BackoffBuilder;
+use crate::backoff::ClassifiedBackoff;
+use crate::backoff::ErrorBackoff;
 use crate::blocking_sleep::MaybeBlockingSleeper;
-use crate::Backoff;
+use crate::instrument::GiveUp;
+use crate::instrument::Instrument;
 use crate::BlockingSleeper;
 use crate::DefaultBlockingSleeper;
+use crate::RetryObserver;
+use crate::RetryableError;
 
 /// BlockingRetryable adds retry support for blocking functions.
 ///
@@ -61,24 +70,29 @@ where
 
 /// Retry structure generated by [`BlockingRetryable`].
 pub struct BlockingRetry<
-    B: Backoff,
+    B: ErrorBackoff<E>,
     T,
     E,
     F: FnMut() -> Result<T, E>,
     SF: MaybeBlockingSleeper = DefaultBlockingSleeper,
     RF = fn(&E) -> bool,
     NF = fn(&E, Duration),
+    AF = fn(&E, Option<Duration>) -> Option<Duration>,
+    OB = (),
 > {
     backoff: B,
     retryable: RF,
     notify: NF,
+    adjust: AF,
     f: F,
     sleep_fn: SF,
+    instrument: Instrument<E>,
+    observer: OB,
 }
 
 impl<B, T, E, F> BlockingRetry<B, T, E, F>
 where
-    B: Backoff,
+    B: ErrorBackoff<E>,
     F: FnMut() -> Result<T, E>,
 {
     /// Create a new retry.
@@ -87,19 +101,23 @@ where
             backoff,
             retryable: |_: &E| true,
             notify: |_: &E, _: Duration| {},
+            adjust: |_: &E, dur: Option<Duration>| dur,
             sleep_fn: DefaultBlockingSleeper::default(),
+            instrument: Instrument::new(),
+            observer: (),
             f,
         }
     }
 }
 
-impl<B, T, E, F, SF, RF, NF> BlockingRetry<B, T, E, F, SF, RF, NF>
+impl<B, T, E, F, SF, RF, NF, AF, OB> BlockingRetry<B, T, E, F, SF, RF, NF, AF, OB>
 where
-    B: Backoff,
+    B: ErrorBackoff<E>,
     F: FnMut() -> Result<T, E>,
     SF: MaybeBlockingSleeper,
     RF: FnMut(&E) -> bool,
     NF: FnMut(&E, Duration),
+    AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
 {
     /// Set the sleeper for retrying.
     ///
@@ -128,13 +146,19 @@ where
     ///     Ok(())
     /// }
     /// ```
-    pub fn sleep<SN: BlockingSleeper>(self, sleep_fn: SN) -> BlockingRetry<B, T, E, F, SN, RF, NF> {
+    pub fn sleep<SN: BlockingSleeper>(
+        self,
+        sleep_fn: SN,
+    ) -> BlockingRetry<B, T, E, F, SN, RF, NF, AF, OB> {
         BlockingRetry {
             backoff: self.backoff,
             retryable: self.retryable,
             notify: self.notify,
+            adjust: self.adjust,
             f: self.f,
             sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
         }
     }
 
@@ -166,13 +190,16 @@ where
     pub fn when<RN: FnMut(&E) -> bool>(
         self,
         retryable: RN,
-    ) -> BlockingRetry<B, T, E, F, SF, RN, NF> {
+    ) -> BlockingRetry<B, T, E, F, SF, RN, NF, AF, OB> {
         BlockingRetry {
             backoff: self.backoff,
             retryable,
             notify: self.notify,
+            adjust: self.adjust,
             f: self.f,
             sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
         }
     }
 
@@ -210,44 +237,292 @@ where
     pub fn notify<NN: FnMut(&E, Duration)>(
         self,
         notify: NN,
-    ) -> BlockingRetry<B, T, E, F, SF, RF, NN> {
+    ) -> BlockingRetry<B, T, E, F, SF, RF, NN, AF, OB> {
         BlockingRetry {
             backoff: self.backoff,
             retryable: self.retryable,
             notify,
+            adjust: self.adjust,
+            f: self.f,
+            sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
+        }
+    }
+
+    /// Attach a [`RetryObserver`] to be notified about every step of this retry.
+    ///
+    /// See [`Retry::observe`](crate::Retry::observe) for details.
+    pub fn observe<ON: RetryObserver<E>>(
+        self,
+        observer: ON,
+    ) -> BlockingRetry<B, T, E, F, SF, RF, NF, AF, ON> {
+        BlockingRetry {
+            backoff: self.backoff,
+            retryable: self.retryable,
+            notify: self.notify,
+            adjust: self.adjust,
+            f: self.f,
+            sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer,
+        }
+    }
+
+    /// Sets the function to adjust the backoff duration for retry attempts.
+    ///
+    /// When a retry occurs, the provided function will be called with the error and the proposed backoff duration, allowing you to modify the final duration used.
+    ///
+    /// If the function returns `None`, it indicates that no further retries should be made, and the error will be returned regardless of the backoff duration provided by the input.
+    ///
+    /// If no `adjust` function is specified, the original backoff duration from the input will be used without modification.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use core::time::Duration;
+    ///
+    /// use anyhow::Result;
+    /// use backon::BlockingRetryable;
+    /// use backon::ExponentialBuilder;
+    ///
+    /// fn fetch() -> Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// fn main() -> Result<()> {
+    ///     let retry = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         // Wait at least one second between attempts.
+    ///         .adjust(|_, dur| dur.map(|dur| dur.max(Duration::from_secs(1))));
+    ///     let content = retry.call()?;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn adjust<NAF: FnMut(&E, Option<Duration>) -> Option<Duration>>(
+        self,
+        adjust: NAF,
+    ) -> BlockingRetry<B, T, E, F, SF, RF, NF, NAF, OB> {
+        BlockingRetry {
+            backoff: self.backoff,
+            retryable: self.retryable,
+            notify: self.notify,
+            adjust,
+            f: self.f,
+            sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
+        }
+    }
+
+    /// Use a different backoff for errors matching `class`.
+    ///
+    /// Errors for which `class` returns `true` sleep with the backoff built from
+    /// `builder`, all other errors keep using the current backoff. Every backoff
+    /// has its own attempt counter and limit, retrying gives up as soon as the
+    /// backoff for the current error is exhausted.
+    ///
+    /// `backoff_for` can be called multiple times, the class added last is checked first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use core::time::Duration;
+    ///
+    /// use backon::BlockingRetryable;
+    /// use backon::ConstantBuilder;
+    /// use backon::ExponentialBuilder;
+    ///
+    /// fn fetch() -> std::io::Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let content = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         // Wait longer if the resource is locked by someone else.
+    ///         .backoff_for(
+    ///             |e: &std::io::Error| e.kind() == std::io::ErrorKind::WouldBlock,
+    ///             ConstantBuilder::default().with_delay(Duration::from_secs(5)),
+    ///         )
+    ///         .call()?;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn backoff_for<P, BB>(
+        self,
+        class: P,
+        builder: BB,
+    ) -> BlockingRetry<ClassifiedBackoff<B, P, BB::Backoff>, T, E, F, SF, RF, NF, AF, OB>
+    where
+        P: FnMut(&E) -> bool + Send + Sync + Unpin,
+        BB: BackoffBuilder,
+    {
+        BlockingRetry {
+            backoff: ClassifiedBackoff::new(self.backoff, class, builder.build()),
+            retryable: self.retryable,
+            notify: self.notify,
+            adjust: self.adjust,
             f: self.f,
             sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
         }
     }
+
+    /// Emit [`tracing`] spans and events for this retry.
+    ///
+    /// See [`Retry::trace`](crate::Retry::trace) for details.
+    #[cfg(feature = "tracing")]
+    pub fn trace(mut self, name: &'static str) -> Self
+    where
+        E: Display + Debug,
+    {
+        self.instrument = self.instrument.with_tracing(name);
+        self
+    }
+
+    /// Record [`metrics`] for this retry, labeled by `policy`.
+    ///
+    /// See [`Retry::metrics`](crate::Retry::metrics) for details.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, policy: impl Into<metrics::SharedString>) -> Self {
+        self.instrument = self.instrument.with_metrics(policy.into());
+        self
+    }
+}
+
+impl<B, T, E, F, SF, RF, NF, AF, OB> BlockingRetry<B, T, E, F, SF, RF, NF, AF, OB>
+where
+    B: ErrorBackoff<E>,
+    E: RetryableError,
+    F: FnMut() -> Result<T, E>,
+    SF: MaybeBlockingSleeper,
+    RF: FnMut(&E) -> bool,
+    NF: FnMut(&E, Duration),
+    AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
+{
+    /// Classify errors with their [`RetryableError`] implementation.
+    ///
+    /// This is a shortcut for `.when(E::is_retryable)` together with an `adjust`
+    /// that sleeps for [`RetryableError::retry_after`] when the error provides one.
+    /// The backoff still decides whether another attempt is allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use backon::BlockingRetryable;
+    /// use backon::ExponentialBuilder;
+    /// use backon::RetryableError;
+    ///
+    /// #[derive(Debug)]
+    /// enum Error {
+    ///     Busy,
+    ///     NotFound,
+    /// }
+    ///
+    /// impl RetryableError for Error {
+    ///     fn is_retryable(&self) -> bool {
+    ///         matches!(self, Error::Busy)
+    ///     }
+    /// }
+    ///
+    /// fn fetch() -> Result<String, Error> {
+    ///     Err(Error::NotFound)
+    /// }
+    ///
+    /// fn main() {
+    ///     let result = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         .when_retryable()
+    ///         .call();
+    ///     assert!(result.is_err());
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn when_retryable(
+        self,
+    ) -> BlockingRetry<
+        B,
+        T,
+        E,
+        F,
+        SF,
+        fn(&E) -> bool,
+        NF,
+        fn(&E, Option<Duration>) -> Option<Duration>,
+        OB,
+    > {
+        self.when(E::is_retryable as fn(&E) -> bool)
+            .adjust(crate::retryable_error::adjust::<E>)
+    }
 }
 
-impl<B, T, E, F, SF, RF, NF> BlockingRetry<B, T, E, F, SF, RF, NF>
+impl<B, T, E, F, SF, RF, NF, AF, OB> BlockingRetry<B, T, E, F, SF, RF, NF, AF, OB>
 where
-    B: Backoff,
+    B: ErrorBackoff<E>,
     F: FnMut() -> Result<T, E>,
     SF: BlockingSleeper,
     RF: FnMut(&E) -> bool,
     NF: FnMut(&E, Duration),
+    AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
+    OB: RetryObserver<E>,
 {
     /// Call the retried function.
     ///
     /// TODO: implement [`FnOnce`] after it stable.
     pub fn call(mut self) -> Result<T, E> {
+        let _span = self.instrument.enter();
         loop {
-            let result = (self.f)();
+            self.instrument.attempt_start();
+            let attempt = self.instrument.attempt();
+            self.observer.on_attempt_start(attempt);
+            let result = {
+                let _attempt = self.instrument.enter_attempt();
+                (self.f)()
+            };
 
             match result {
-                Ok(v) => return Ok(v),
+                Ok(v) => {
+                    self.instrument.success();
+                    self.observer.on_success(attempt);
+                    return Ok(v);
+                }
                 Err(err) => {
+                    self.observer.on_attempt_error(attempt, &err);
                     if !(self.retryable)(&err) {
+                        self.instrument.give_up(&err, GiveUp::NotRetryable);
+                        self.observer.on_give_up(attempt, &err);
                         return Err(err);
                     }
 
-                    match self.backoff.next() {
-                        None => return Err(err),
+                    match (self.adjust)(&err, self.backoff.next_for(&err)) {
+                        None => {
+                            self.instrument.give_up(&err, GiveUp::Exhausted);
+                            self.observer.on_give_up(attempt, &err);
+                            return Err(err);
+                        }
                         Some(dur) => {
+                            // Stop with the last error once the sleeper has been interrupted.
+                            if self.sleep_fn.is_interrupted() {
+                                self.instrument.give_up(&err, GiveUp::Interrupted);
+                                self.observer.on_give_up(attempt, &err);
+                                return Err(err);
+                            }
+                            self.instrument.retry(&err, dur);
+                            self.observer.on_retry_scheduled(attempt, &err, dur);
                             (self.notify)(&err, dur);
                             self.sleep_fn.sleep(dur);
+                            if self.sleep_fn.is_interrupted() {
+                                self.instrument.give_up(&err, GiveUp::Interrupted);
+                                self.observer.on_give_up(attempt, &err);
+                                return Err(err);
+                            }
                         }
                     }
                 }
@@ -362,4 +637,101 @@ mod tests {
         assert_eq!(calls_notify.len(), 3);
         Ok(())
     }
+
+    #[test]
+    fn test_retry_with_adjust() {
+        let mut sleeps = vec![];
+
+        let f = || Err::<(), anyhow::Error>(anyhow::anyhow!("retryable"));
+
+        let result = f
+            .retry(ExponentialBuilder::default().with_min_delay(Duration::from_millis(1)))
+            .sleep(|_| {})
+            .notify(|_, dur| sleeps.push(dur))
+            // Stop after the first retry.
+            .adjust(|_, dur| dur.filter(|dur| *dur < Duration::from_millis(2)))
+            .call();
+
+        assert!(result.is_err());
+        assert_eq!(vec![Duration::from_millis(1)], sleeps);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Busy;
+
+    impl RetryableError for Busy {
+        fn is_retryable(&self) -> bool {
+            true
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            Some(Duration::from_millis(5))
+        }
+    }
+
+    #[test]
+    fn test_retry_when_retryable() {
+        let mut sleeps = vec![];
+
+        let result = (|| Err::<(), _>(Busy))
+            .retry(ExponentialBuilder::default().with_max_times(2))
+            .sleep(|_| {})
+            .notify(|_, dur| sleeps.push(dur))
+            .when_retryable()
+            .call();
+
+        assert_eq!(Err(Busy), result);
+        assert_eq!(vec![Duration::from_millis(5); 2], sleeps);
+    }
+
+    #[test]
+    fn test_retry_with_backoff_for() {
+        let mut attempts = 0;
+
+        let result = (|| {
+            attempts += 1;
+            Err::<(), _>(attempts)
+        })
+        .retry(ExponentialBuilder::default().with_max_times(1))
+        .backoff_for(
+            |e: &i32| *e <= 3,
+            crate::ConstantBuilder::default().with_max_times(10),
+        )
+        .sleep(|_| {})
+        .call();
+
+        // Attempts 1..=3 use the constant backoff, attempt 4 retries once with
+        // the exponential backoff and attempt 5 exhausts it.
+        assert_eq!(Err(5), result);
+    }
+
+    #[cfg(feature = "std-blocking-sleep")]
+    #[test]
+    fn test_retry_with_interrupted_sleeper() {
+        use std::thread;
+
+        let sleeper = crate::CondvarSleeper::new();
+        let handle = sleeper.clone();
+        let interrupter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            handle.interrupt();
+        });
+
+        let mut attempts = 0;
+        let result = (|| {
+            attempts += 1;
+            Err::<(), _>(attempts)
+        })
+        .retry(
+            crate::ConstantBuilder::default()
+                .with_delay(Duration::from_secs(60))
+                .without_max_times(),
+        )
+        .sleep(sleeper)
+        .call();
+        interrupter.join().unwrap();
+
+        // The first sleep is interrupted, the error of the first attempt is returned.
+        assert_eq!(Err(1), result);
+    }
 }
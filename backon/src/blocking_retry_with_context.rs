@@ -1,10 +1,17 @@
+#[cfg(feature = "tracing")]
+use core::fmt::Debug;
+#[cfg(feature = "tracing")]
+use core::fmt::Display;
 use core::time::Duration;
 
 use crate::backoff::BackoffBuilder;
 use crate::blocking_sleep::MaybeBlockingSleeper;
+use crate::instrument::GiveUp;
+use crate::instrument::Instrument;
 use crate::Backoff;
 use crate::BlockingSleeper;
 use crate::DefaultBlockingSleeper;
+use crate::RetryObserver;
 
 /// BlockingRetryableWithContext adds retry support for blocking functions.
 pub trait BlockingRetryableWithContext<
@@ -39,12 +46,15 @@ pub struct BlockingRetryWithContext<
     SF: MaybeBlockingSleeper = DefaultBlockingSleeper,
     RF = fn(&E) -> bool,
     NF = fn(&E, Duration),
+    OB = (),
 > {
     backoff: B,
     retryable: RF,
     notify: NF,
     f: F,
     sleep_fn: SF,
+    instrument: Instrument<E>,
+    observer: OB,
     ctx: Option<Ctx>,
 }
 
@@ -60,13 +70,15 @@ where
             retryable: |_: &E| true,
             notify: |_: &E, _: Duration| {},
             sleep_fn: DefaultBlockingSleeper::default(),
+            instrument: Instrument::new(),
+            observer: (),
             f,
             ctx: None,
         }
     }
 }
 
-impl<B, T, E, Ctx, F, SF, RF, NF> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF>
+impl<B, T, E, Ctx, F, SF, RF, NF, OB> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF, OB>
 where
     B: Backoff,
     F: FnMut(Ctx) -> (Ctx, Result<T, E>),
@@ -77,13 +89,18 @@ where
     /// Set the context for retrying.
     ///
     /// Context is used to capture ownership manually to prevent lifetime issues.
-    pub fn context(self, context: Ctx) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF> {
+    pub fn context(
+        self,
+        context: Ctx,
+    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF, OB> {
         BlockingRetryWithContext {
             backoff: self.backoff,
             retryable: self.retryable,
             notify: self.notify,
             f: self.f,
             sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
             ctx: Some(context),
         }
     }
@@ -96,13 +113,15 @@ where
     pub fn sleep<SN: BlockingSleeper>(
         self,
         sleep_fn: SN,
-    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SN, RF, NF> {
+    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SN, RF, NF, OB> {
         BlockingRetryWithContext {
             backoff: self.backoff,
             retryable: self.retryable,
             notify: self.notify,
             f: self.f,
             sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
             ctx: self.ctx,
         }
     }
@@ -113,13 +132,15 @@ where
     pub fn when<RN: FnMut(&E) -> bool>(
         self,
         retryable: RN,
-    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RN, NF> {
+    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RN, NF, OB> {
         BlockingRetryWithContext {
             backoff: self.backoff,
             retryable,
             notify: self.notify,
             f: self.f,
             sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
             ctx: self.ctx,
         }
     }
@@ -132,48 +153,122 @@ where
     pub fn notify<NN: FnMut(&E, Duration)>(
         self,
         notify: NN,
-    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NN> {
+    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NN, OB> {
         BlockingRetryWithContext {
             backoff: self.backoff,
             retryable: self.retryable,
             notify,
             f: self.f,
             sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer: self.observer,
             ctx: self.ctx,
         }
     }
+
+    /// Attach a [`RetryObserver`] to be notified about every step of this retry.
+    ///
+    /// See [`Retry::observe`](crate::Retry::observe) for details.
+    pub fn observe<ON: RetryObserver<E>>(
+        self,
+        observer: ON,
+    ) -> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF, ON> {
+        BlockingRetryWithContext {
+            backoff: self.backoff,
+            retryable: self.retryable,
+            notify: self.notify,
+            f: self.f,
+            sleep_fn: self.sleep_fn,
+            instrument: self.instrument,
+            observer,
+            ctx: self.ctx,
+        }
+    }
+
+    /// Emit [`tracing`] spans and events for this retry.
+    ///
+    /// See [`Retry::trace`](crate::Retry::trace) for details.
+    #[cfg(feature = "tracing")]
+    pub fn trace(mut self, name: &'static str) -> Self
+    where
+        E: Display + Debug,
+    {
+        self.instrument = self.instrument.with_tracing(name);
+        self
+    }
+
+    /// Record [`metrics`] for this retry, labeled by `policy`.
+    ///
+    /// See [`Retry::metrics`](crate::Retry::metrics) for details.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, policy: impl Into<metrics::SharedString>) -> Self {
+        self.instrument = self.instrument.with_metrics(policy.into());
+        self
+    }
 }
 
-impl<B, T, E, Ctx, F, SF, RF, NF> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF>
+impl<B, T, E, Ctx, F, SF, RF, NF, OB> BlockingRetryWithContext<B, T, E, Ctx, F, SF, RF, NF, OB>
 where
     B: Backoff,
     F: FnMut(Ctx) -> (Ctx, Result<T, E>),
     SF: BlockingSleeper,
     RF: FnMut(&E) -> bool,
     NF: FnMut(&E, Duration),
+    OB: RetryObserver<E>,
 {
     /// Call the retried function.
     ///
     /// TODO: implement [`FnOnce`] after it stable.
     pub fn call(mut self) -> (Ctx, Result<T, E>) {
         let mut ctx = self.ctx.take().expect("context must be valid");
+        let _span = self.instrument.enter();
         loop {
-            let (xctx, result) = (self.f)(ctx);
+            self.instrument.attempt_start();
+            let attempt = self.instrument.attempt();
+            self.observer.on_attempt_start(attempt);
+            let (xctx, result) = {
+                let _attempt = self.instrument.enter_attempt();
+                (self.f)(ctx)
+            };
             // return ctx ownership back
             ctx = xctx;
 
             match result {
-                Ok(v) => return (ctx, Ok(v)),
+                Ok(v) => {
+                    self.instrument.success();
+                    self.observer.on_success(attempt);
+                    return (ctx, Ok(v));
+                }
                 Err(err) => {
+                    self.observer.on_attempt_error(attempt, &err);
                     if !(self.retryable)(&err) {
+                        self.instrument.give_up(&err, GiveUp::NotRetryable);
+                        self.observer.on_give_up(attempt, &err);
                         return (ctx, Err(err));
                     }
 
                     match self.backoff.next() {
-                        None => return (ctx, Err(err)),
+                        None => {
+                            self.instrument.give_up(&err, GiveUp::Exhausted);
+                            self.observer.on_give_up(attempt, &err);
+                            return (ctx, Err(err));
+                        }
                         Some(dur) => {
+                            // Stop with the last error once the sleeper has been interrupted.
+                            if self.sleep_fn.is_interrupted() {
+                                self.instrument.give_up(&err, GiveUp::Interrupted);
+                                self.observer.on_give_up(attempt, &err);
+                                return (ctx, Err(err));
+                            }
+                            self.instrument.retry(&err, dur);
+                            self.observer.on_retry_scheduled(attempt, &err, dur);
                             (self.notify)(&err, dur);
                             self.sleep_fn.sleep(dur);
+                            if self.sleep_fn.is_interrupted() {
+                                self.instrument.give_up(&err, GiveUp::Interrupted);
+                                self.observer.on_give_up(attempt, &err);
+                                return (ctx, Err(err));
+                            }
                         }
                     }
                 }
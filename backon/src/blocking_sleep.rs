@@ -1,9 +1,25 @@
 use core::time::Duration;
+#[cfg(feature = "std-blocking-sleep")]
+use std::sync::Arc;
+#[cfg(feature = "std-blocking-sleep")]
+use std::sync::Condvar;
+#[cfg(feature = "std-blocking-sleep")]
+use std::sync::Mutex;
+#[cfg(feature = "std-blocking-sleep")]
+use std::sync::PoisonError;
 
 /// A sleeper is used sleep for a specified duration.
 pub trait BlockingSleeper: 'static {
     /// sleep for a specified duration.
     fn sleep(&self, dur: Duration);
+
+    /// Returns `true` if the sleeper has been interrupted.
+    ///
+    /// Retrying stops with the last error once the sleeper is interrupted, instead
+    /// of starting another attempt. Default to `false`.
+    fn is_interrupted(&self) -> bool {
+        false
+    }
 }
 
 /// A stub trait allowing non-[`BlockingSleeper`] types to be used as a generic parameter in [`BlockingRetry`][crate::BlockingRetry].
@@ -37,6 +53,7 @@ pub type DefaultBlockingSleeper = StdSleeper;
 /// Users should enable a feature of this crate that provides a valid [`Sleeper`] implementation when this type appears in compilation errors. Alternatively, a custom [`Sleeper`] implementation should be provided where necessary, such as in [`crate::Retry::sleeper`].
 #[doc(hidden)]
 #[derive(Clone, Copy, Debug, Default)]
+#[allow(dead_code)]
 pub struct PleaseEnableAFeatureOrProvideACustomSleeper;
 
 /// Implement `MaybeSleeper` but not `Sleeper`.
@@ -53,3 +70,111 @@ impl BlockingSleeper for StdSleeper {
         std::thread::sleep(dur)
     }
 }
+
+/// CondvarSleeper is a [`BlockingSleeper`] that can be interrupted from other threads.
+///
+/// All clones share the same state, so a clone kept by another thread works as a
+/// handle: calling [`CondvarSleeper::interrupt`] wakes up the current sleep and
+/// makes all future sleeps return immediately. A [`BlockingRetry`](crate::BlockingRetry)
+/// using an interrupted sleeper stops with the last error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io;
+/// use std::thread;
+///
+/// use backon::BlockingRetryable;
+/// use backon::CondvarSleeper;
+/// use backon::ExponentialBuilder;
+///
+/// fn fetch() -> io::Result<String> {
+///     Err(io::ErrorKind::ConnectionRefused.into())
+/// }
+///
+/// let sleeper = CondvarSleeper::new();
+///
+/// let handle = sleeper.clone();
+/// let worker = thread::spawn(move || {
+///     fetch
+///         .retry(ExponentialBuilder::default().without_max_times())
+///         .sleep(handle)
+///         .call()
+/// });
+///
+/// // Shutting down, the worker returns the last error instead of sleeping.
+/// sleeper.interrupt();
+/// assert!(worker.join().unwrap().is_err());
+/// ```
+#[cfg(feature = "std-blocking-sleep")]
+#[derive(Clone, Debug, Default)]
+pub struct CondvarSleeper {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+#[cfg(feature = "std-blocking-sleep")]
+impl CondvarSleeper {
+    /// Create a new sleeper that hasn't been interrupted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interrupt the current and all future sleeps.
+    pub fn interrupt(&self) {
+        let (interrupted, cvar) = &*self.inner;
+        *interrupted.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        cvar.notify_all();
+    }
+}
+
+#[cfg(feature = "std-blocking-sleep")]
+impl BlockingSleeper for CondvarSleeper {
+    fn sleep(&self, dur: Duration) {
+        let (interrupted, cvar) = &*self.inner;
+        let guard = interrupted.lock().unwrap_or_else(PoisonError::into_inner);
+        // `wait_timeout_while` handles spurious wakeups and keeps track of the remaining time.
+        let _ = cvar
+            .wait_timeout_while(guard, dur, |interrupted| !*interrupted)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+
+    fn is_interrupted(&self) -> bool {
+        let (interrupted, _) = &*self.inner;
+        *interrupted.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(all(test, feature = "std-blocking-sleep"))]
+mod tests {
+    use std::thread;
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn test_condvar_sleeper() {
+        let sleeper = CondvarSleeper::new();
+        assert!(!sleeper.is_interrupted());
+
+        let start = Instant::now();
+        sleeper.sleep(Duration::from_millis(10));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_condvar_sleeper_interrupt() {
+        let sleeper = CondvarSleeper::new();
+
+        let handle = sleeper.clone();
+        let start = Instant::now();
+        let waiter = thread::spawn(move || handle.sleep(Duration::from_secs(60)));
+        thread::sleep(Duration::from_millis(10));
+        sleeper.interrupt();
+        waiter.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(60));
+
+        // Future sleeps return immediately.
+        assert!(sleeper.is_interrupted());
+        sleeper.sleep(Duration::from_secs(60));
+    }
+}
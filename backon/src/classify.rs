@@ -0,0 +1,364 @@
+//! Ready-made predicates to decide whether an error is worth retrying.
+//!
+//! Every function in this module can be passed to `when` directly:
+//!
+//! ```no_run
+//! use std::fs::File;
+//!
+//! use backon::classify;
+//! use backon::BlockingRetryable;
+//! use backon::ExponentialBuilder;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let file = (|| File::open("/tmp/backon"))
+//!         .retry(ExponentialBuilder::default())
+//!         .when(classify::io_error)
+//!         .call()?;
+//!     println!("opened file: {:?}", file);
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Only errors that are likely to go away by themselves are considered
+//! transient. Errors caused by the request itself, like invalid input or
+//! permission problems, are never retried.
+//!
+//! Predicates for ecosystem crates are gated under the feature with the same
+//! name as the crate: `reqwest`, `hyper`, `sqlx` and `tonic`.
+
+use std::io;
+
+/// Returns `true` if the [`io::Error`] is transient.
+///
+/// The following kinds are considered transient:
+///
+/// - [`io::ErrorKind::TimedOut`]
+/// - [`io::ErrorKind::Interrupted`]
+/// - [`io::ErrorKind::WouldBlock`]
+/// - [`io::ErrorKind::ConnectionRefused`]
+/// - [`io::ErrorKind::ConnectionReset`]
+/// - [`io::ErrorKind::ConnectionAborted`]
+/// - [`io::ErrorKind::NotConnected`]
+/// - [`io::ErrorKind::BrokenPipe`]
+pub fn io_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::TimedOut
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+    )
+}
+
+/// Returns `true` if the [`reqwest::Error`] is transient.
+///
+/// Timeouts, connection failures and transient IO errors in the source chain
+/// are retried. Errors created by `error_for_status` are retried for
+/// `408 Request Timeout`, `429 Too Many Requests`, `500 Internal Server Error`,
+/// `502 Bad Gateway`, `503 Service Unavailable` and `504 Gateway Timeout`.
+///
+/// Builder, redirect, body and decode errors are never retried.
+#[cfg(feature = "reqwest")]
+pub fn reqwest_error(err: &reqwest::Error) -> bool {
+    if err.is_builder() || err.is_redirect() || err.is_body() || err.is_decode() {
+        return false;
+    }
+    if err.is_timeout() || err.is_connect() {
+        return true;
+    }
+    if let Some(status) = err.status() {
+        return matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504);
+    }
+    source_io_error(err)
+}
+
+/// Returns `true` if the [`hyper::Error`] is transient.
+///
+/// Canceled requests, closed connections, incomplete messages, timeouts and
+/// transient IO errors in the source chain are retried. Parse and user errors
+/// are never retried.
+#[cfg(feature = "hyper")]
+pub fn hyper_error(err: &hyper::Error) -> bool {
+    if err.is_parse() || err.is_user() {
+        return false;
+    }
+    err.is_canceled()
+        || err.is_closed()
+        || err.is_incomplete_message()
+        || err.is_timeout()
+        || source_io_error(err)
+}
+
+/// Returns `true` if the [`sqlx::Error`] is transient.
+///
+/// The following errors are retried:
+///
+/// - Transient IO errors, see [`io_error`].
+/// - Pool timeouts and crashed background workers.
+/// - Database errors with SQLSTATE class `08` (connection exception), `40001`
+///   (serialization failure), `40P01` (deadlock detected), `53300` (too many
+///   connections) and `57P01`~`57P03` (server shutting down or starting up).
+/// - SQLite errors with primary code `SQLITE_BUSY` or `SQLITE_LOCKED`.
+#[cfg(feature = "sqlx")]
+pub fn sqlx_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(err) => io_error(err),
+        sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(err) => match err.code() {
+            Some(code) => is_transient_database_code(&code),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(feature = "sqlx")]
+fn is_transient_database_code(code: &str) -> bool {
+    // SQLSTATE codes used by PostgreSQL and MySQL always have 5 characters.
+    if code.len() == 5 {
+        return code.starts_with("08")
+            || matches!(
+                code,
+                "40001" | "40P01" | "53300" | "57P01" | "57P02" | "57P03"
+            );
+    }
+
+    // SQLite reports (extended) result codes, the primary code lives in the lowest byte.
+    const SQLITE_BUSY: u32 = 5;
+    const SQLITE_LOCKED: u32 = 6;
+    match code.parse::<u32>() {
+        Ok(code) => matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED),
+        Err(_) => false,
+    }
+}
+
+/// Returns `true` if the [`tonic::Status`] is transient.
+///
+/// The codes `Unavailable`, `ResourceExhausted` and `Aborted` are retried.
+///
+/// `DeadlineExceeded` is not retried: the deadline is usually shared by all
+/// attempts, so there is no time left for another one.
+#[cfg(feature = "tonic")]
+pub fn tonic_status(status: &tonic::Status) -> bool {
+    matches!(
+        status.code(),
+        tonic::Code::Unavailable | tonic::Code::ResourceExhausted | tonic::Code::Aborted
+    )
+}
+
+/// Returns `true` if the first [`io::Error`] in the source chain of `err` is transient.
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+fn source_io_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<io::Error>() {
+            return io_error(err);
+        }
+        source = err.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error() {
+        for kind in [
+            io::ErrorKind::TimedOut,
+            io::ErrorKind::Interrupted,
+            io::ErrorKind::WouldBlock,
+            io::ErrorKind::ConnectionRefused,
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::ConnectionAborted,
+            io::ErrorKind::NotConnected,
+            io::ErrorKind::BrokenPipe,
+        ] {
+            assert!(io_error(&kind.into()), "{kind:?} should be transient");
+        }
+
+        for kind in [
+            io::ErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied,
+            io::ErrorKind::InvalidInput,
+            io::ErrorKind::InvalidData,
+            io::ErrorKind::AlreadyExists,
+            io::ErrorKind::Unsupported,
+        ] {
+            assert!(!io_error(&kind.into()), "{kind:?} should be permanent");
+        }
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_database_code() {
+        // PostgreSQL
+        assert!(is_transient_database_code("08006"));
+        assert!(is_transient_database_code("40001"));
+        assert!(is_transient_database_code("40P01"));
+        assert!(is_transient_database_code("57P03"));
+        assert!(!is_transient_database_code("23505"));
+        assert!(!is_transient_database_code("42P01"));
+
+        // SQLite
+        assert!(is_transient_database_code("5"));
+        assert!(is_transient_database_code("6"));
+        // SQLITE_BUSY_SNAPSHOT
+        assert!(is_transient_database_code("517"));
+        // SQLITE_CONSTRAINT_UNIQUE
+        assert!(!is_transient_database_code("2067"));
+        assert!(!is_transient_database_code("not a code"));
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_sqlx_error() {
+        assert!(sqlx_error(&sqlx::Error::PoolTimedOut));
+        assert!(sqlx_error(&sqlx::Error::Io(
+            io::ErrorKind::ConnectionReset.into()
+        )));
+        assert!(!sqlx_error(&sqlx::Error::PoolClosed));
+        assert!(!sqlx_error(&sqlx::Error::RowNotFound));
+    }
+
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    #[tokio::test]
+    async fn test_reqwest_error() {
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        // A relative URL can't be sent at all.
+        let err = client.get("/relative").build().unwrap_err();
+        assert!(err.is_builder());
+        assert!(!reqwest_error(&err));
+
+        // Bind and drop a listener to get a port that refuses connections.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let err = client
+            .get(std::format!("http://{addr}"))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.is_connect());
+        assert!(reqwest_error(&err));
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "http"))]
+    #[test]
+    fn test_reqwest_status() {
+        let error_for_status = |status: u16| {
+            let resp = http::Response::builder().status(status).body("").unwrap();
+            reqwest::Response::from(resp)
+                .error_for_status()
+                .unwrap_err()
+        };
+
+        assert!(reqwest_error(&error_for_status(503)));
+        assert!(reqwest_error(&error_for_status(429)));
+        assert!(!reqwest_error(&error_for_status(404)));
+        assert!(!reqwest_error(&error_for_status(501)));
+    }
+
+    #[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+    mod hyper_conn {
+        use core::pin::Pin;
+        use core::task::Context;
+        use core::task::Poll;
+        use core::task::Waker;
+
+        use super::*;
+
+        /// An in-memory connection that replies with `response` once a request has been
+        /// written, and then closes.
+        struct MockConn {
+            response: &'static [u8],
+            written: bool,
+            reader: Option<Waker>,
+        }
+
+        impl hyper::rt::Read for MockConn {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                mut buf: hyper::rt::ReadBufCursor<'_>,
+            ) -> Poll<io::Result<()>> {
+                if !self.written {
+                    self.reader = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                let n = self.response.len().min(buf.remaining());
+                buf.put_slice(&self.response[..n]);
+                self.response = &self.response[n..];
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        impl hyper::rt::Write for MockConn {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.written = true;
+                if let Some(reader) = self.reader.take() {
+                    reader.wake();
+                }
+                Poll::Ready(Ok(buf.len()))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        async fn hyper_send(response: &'static [u8]) -> hyper::Error {
+            let (mut sender, conn) = hyper::client::conn::http1::handshake(MockConn {
+                response,
+                written: false,
+                reader: None,
+            })
+            .await
+            .unwrap();
+            let (res, _) = tokio::join!(
+                sender.send_request(hyper::Request::new(std::string::String::new())),
+                conn
+            );
+            res.unwrap_err()
+        }
+
+        #[tokio::test]
+        async fn test_hyper_error() {
+            let err = hyper_send(b"not http\r\n\r\n").await;
+            assert!(err.is_parse());
+            assert!(!hyper_error(&err));
+
+            // The connection is closed before a response arrives.
+            let err = hyper_send(b"").await;
+            assert!(err.is_incomplete_message());
+            assert!(hyper_error(&err));
+        }
+    }
+
+    #[cfg(feature = "tonic")]
+    #[test]
+    fn test_tonic_status() {
+        assert!(tonic_status(&tonic::Status::unavailable("down")));
+        assert!(tonic_status(&tonic::Status::resource_exhausted("quota")));
+        assert!(tonic_status(&tonic::Status::aborted("conflict")));
+        assert!(!tonic_status(&tonic::Status::deadline_exceeded("late")));
+        assert!(!tonic_status(&tonic::Status::invalid_argument("bad")));
+        assert!(!tonic_status(&tonic::Status::not_found("missing")));
+    }
+}
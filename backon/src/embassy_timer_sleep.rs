@@ -1,5 +1,8 @@
 use core::time::Duration;
 
+use embassy_time::Instant;
+use embassy_time::TICK_HZ;
+
 use crate::BlockingSleeper;
 use crate::Sleeper;
 
@@ -11,12 +14,52 @@ impl Sleeper for EmbassySleeper {
     type Sleep = embassy_time::Timer;
 
     fn sleep(&self, dur: Duration) -> Self::Sleep {
-        embassy_time::Timer::after_millis(dur.as_millis() as u64)
+        embassy_time::Timer::at(deadline(dur))
     }
 }
 
 impl BlockingSleeper for EmbassySleeper {
     fn sleep(&self, dur: Duration) {
-        embassy_time::block_for(embassy_time::Duration::from_millis(dur.as_millis() as u64));
+        let expires_at = deadline(dur);
+        while Instant::now() < expires_at {}
+    }
+}
+
+/// Convert `dur` into embassy ticks.
+///
+/// The ticks are rounded up so that a non-zero delay never becomes zero, and
+/// saturate at the largest duration embassy can represent.
+fn to_embassy_duration(dur: Duration) -> embassy_time::Duration {
+    let nanos = dur.as_nanos().saturating_mul(TICK_HZ as u128);
+    let ticks = nanos / 1_000_000_000 + u128::from(nanos % 1_000_000_000 != 0);
+    embassy_time::Duration::from_ticks(u64::try_from(ticks).unwrap_or(u64::MAX))
+}
+
+/// Returns the instant `dur` from now, saturating at [`Instant::MAX`].
+fn deadline(dur: Duration) -> Instant {
+    Instant::now()
+        .checked_add(to_embassy_duration(dur))
+        .unwrap_or(Instant::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_embassy_duration() {
+        assert_eq!(0, to_embassy_duration(Duration::ZERO).as_ticks());
+        assert_eq!(
+            TICK_HZ,
+            to_embassy_duration(Duration::from_secs(1)).as_ticks()
+        );
+        assert_eq!(
+            (250 * TICK_HZ + 999_999) / 1_000_000,
+            to_embassy_duration(Duration::from_micros(250)).as_ticks()
+        );
+        // Sub-tick delays are rounded up instead of truncated to zero.
+        assert_eq!(1, to_embassy_duration(Duration::from_nanos(1)).as_ticks());
+        // Very large delays saturate instead of wrapping.
+        assert_eq!(u64::MAX, to_embassy_duration(Duration::MAX).as_ticks());
     }
 }
@@ -0,0 +1,355 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::ready;
+use core::task::Context;
+use core::task::Poll;
+use std::vec::Vec;
+
+/// Endpoints rotates through a list of endpoints for [`RetryWithContext`](crate::RetryWithContext).
+///
+/// Every failed attempt advances to the next endpoint, while a successful attempt
+/// keeps the current one. Endpoints are picked in round-robin order, or in smooth
+/// weighted round-robin order if created by [`Endpoints::weighted`].
+///
+/// An endpoint that fails `max_failures` times in a row is ejected for the next
+/// `ejection_attempts` attempts. If all endpoints are ejected, ejected endpoints
+/// are used anyway.
+///
+/// The state is kept inside `Endpoints`, so it can be reused across retries by
+/// passing the context returned by the previous retry.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use backon::Endpoints;
+/// use backon::ExponentialBuilder;
+/// use backon::RetryableWithContext;
+///
+/// async fn query(replica: String) -> Result<String> {
+///     Ok(format!("hello from {replica}"))
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<()> {
+///     let replicas = Endpoints::new(["replica-1", "replica-2", "replica-3"].map(String::from));
+///
+///     let (replicas, result) = Endpoints::rotate(query)
+///         .retry(ExponentialBuilder::default())
+///         .context(replicas)
+///         .await;
+///     println!("query succeeded: {}", result?);
+///     println!("next query starts from: {}", replicas.current());
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Endpoints<A> {
+    slots: Vec<Slot<A>>,
+    current: usize,
+    max_failures: usize,
+    ejection_attempts: usize,
+
+    /// The number of attempts reported so far.
+    attempts: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Slot<A> {
+    endpoint: A,
+    weight: isize,
+    current_weight: isize,
+    failures: usize,
+    ejected_until: usize,
+}
+
+impl<A> Endpoints<A> {
+    /// Create endpoints which are picked in round-robin order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `endpoints` is empty.
+    pub fn new(endpoints: impl IntoIterator<Item = A>) -> Self {
+        Self::weighted(endpoints.into_iter().map(|endpoint| (endpoint, 1)))
+    }
+
+    /// Create endpoints which are picked in smooth weighted round-robin order.
+    ///
+    /// An endpoint with weight `3` is picked three times as often as an
+    /// endpoint with weight `1`, with picks spread out instead of in bursts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `endpoints` is empty, any weight is zero or larger than `isize::MAX`.
+    pub fn weighted(endpoints: impl IntoIterator<Item = (A, usize)>) -> Self {
+        let slots: Vec<_> = endpoints
+            .into_iter()
+            .map(|(endpoint, weight)| {
+                assert!(weight > 0, "weight of endpoints must be greater than zero");
+                Slot {
+                    endpoint,
+                    weight: isize::try_from(weight)
+                        .expect("weight of endpoints must not exceed isize::MAX"),
+                    current_weight: 0,
+                    failures: 0,
+                    ejected_until: 0,
+                }
+            })
+            .collect();
+        assert!(!slots.is_empty(), "endpoints must not be empty");
+
+        let mut endpoints = Self {
+            slots,
+            current: 0,
+            max_failures: 3,
+            ejection_attempts: 10,
+            attempts: 0,
+        };
+        endpoints.advance();
+        endpoints
+    }
+
+    /// Set the number of consecutive failures before an endpoint is ejected.
+    ///
+    /// Default to 3. Setting it to `0` disables ejection.
+    pub fn with_max_failures(mut self, max_failures: usize) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Set the number of attempts an ejected endpoint is skipped for.
+    ///
+    /// Default to 10. Setting it to `0` disables ejection.
+    pub fn with_ejection_attempts(mut self, ejection_attempts: usize) -> Self {
+        self.ejection_attempts = ejection_attempts;
+        self
+    }
+
+    /// Returns the endpoint to use for the next attempt.
+    pub fn current(&self) -> &A {
+        &self.slots[self.current].endpoint
+    }
+
+    /// Report the outcome of an attempt against [`Endpoints::current`].
+    ///
+    /// A failure advances to the next endpoint, ejecting the current one if it has
+    /// failed too many times in a row.
+    pub fn report(&mut self, success: bool) {
+        self.attempts += 1;
+
+        let slot = &mut self.slots[self.current];
+        if success {
+            slot.failures = 0;
+            return;
+        }
+
+        slot.failures += 1;
+        if self.max_failures > 0 && slot.failures >= self.max_failures {
+            slot.failures = 0;
+            slot.ejected_until = self.attempts + self.ejection_attempts;
+        }
+        self.advance();
+    }
+
+    /// Returns `true` if the endpoint at `index` is ejected right now.
+    fn is_ejected(&self, index: usize) -> bool {
+        self.slots[index].ejected_until > self.attempts
+    }
+
+    /// Pick the next endpoint with smooth weighted round-robin.
+    fn advance(&mut self) {
+        let all_ejected = (0..self.slots.len()).all(|idx| self.is_ejected(idx));
+
+        let mut total = 0;
+        let mut picked: Option<usize> = None;
+        for idx in 0..self.slots.len() {
+            if !all_ejected && self.is_ejected(idx) {
+                continue;
+            }
+
+            let slot = &mut self.slots[idx];
+            slot.current_weight += slot.weight;
+            total += slot.weight;
+            match picked {
+                Some(p) if self.slots[p].current_weight >= self.slots[idx].current_weight => {}
+                _ => picked = Some(idx),
+            }
+        }
+
+        let picked = picked.expect("at least one endpoint must be available");
+        self.slots[picked].current_weight -= total;
+        self.current = picked;
+    }
+}
+
+impl<A: Clone> Endpoints<A> {
+    /// Build a function for [`RetryableWithContext`](crate::RetryableWithContext) that
+    /// calls `f` with the current endpoint and reports the outcome of every attempt.
+    pub fn rotate<T, E, Fut, F>(mut f: F) -> impl FnMut(Self) -> Rotate<A, Fut>
+    where
+        Fut: Future<Output = Result<T, E>>,
+        F: FnMut(A) -> Fut,
+    {
+        move |endpoints: Self| {
+            let fut = f(endpoints.current().clone());
+            Rotate {
+                endpoints: Some(endpoints),
+                fut,
+            }
+        }
+    }
+}
+
+/// Rotate is the future of a single attempt built by [`Endpoints::rotate`].
+pub struct Rotate<A, Fut> {
+    endpoints: Option<Endpoints<A>>,
+    fut: Fut,
+}
+
+impl<A, T, E, Fut> Future for Rotate<A, Fut>
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    type Output = (Endpoints<A>, Result<T, E>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: This is safe because we don't move the `Rotate` struct itself,
+        // only its internal state.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        // Safety: This is safe because we don't move the `Rotate` struct and this fut.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let fut = unsafe { Pin::new_unchecked(&mut this.fut) };
+        let result = ready!(fut.poll(cx));
+
+        let mut endpoints = this
+            .endpoints
+            .take()
+            .expect("Rotate must not be polled after completion");
+        endpoints.report(result.is_ok());
+        Poll::Ready((endpoints, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn picks<A: Clone>(endpoints: &mut Endpoints<A>, n: usize) -> Vec<A> {
+        (0..n)
+            .map(|_| {
+                let v = endpoints.current().clone();
+                endpoints.report(false);
+                v
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let mut endpoints = Endpoints::new(["a", "b", "c"]).with_ejection_attempts(0);
+        assert_eq!(vec!["a", "b", "c", "a", "b"], picks(&mut endpoints, 5));
+
+        // Success keeps the current endpoint.
+        endpoints.report(true);
+        assert_eq!(&"c", endpoints.current());
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut endpoints = Endpoints::weighted([("a", 3), ("b", 1)]).with_ejection_attempts(0);
+        assert_eq!(
+            vec!["a", "a", "b", "a", "a", "a", "b", "a"],
+            picks(&mut endpoints, 8)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "weight of endpoints must not exceed isize::MAX")]
+    fn test_weight_overflow() {
+        Endpoints::weighted([("a", usize::MAX)]);
+    }
+
+    #[test]
+    fn test_ejection() {
+        let mut endpoints = Endpoints::new(["a", "b", "c"])
+            .with_max_failures(1)
+            .with_ejection_attempts(2);
+
+        // `a` fails and is ejected for 2 attempts.
+        assert_eq!(&"a", endpoints.current());
+        endpoints.report(false);
+        assert_eq!(&"b", endpoints.current());
+        endpoints.report(true);
+        assert_eq!(&"b", endpoints.current());
+        // `b` fails and is ejected, `c` is next in order.
+        endpoints.report(false);
+        assert_eq!(&"c", endpoints.current());
+        // `c` fails as well, only `a` is available.
+        endpoints.report(false);
+        assert_eq!(&"a", endpoints.current());
+    }
+
+    #[test]
+    fn test_zero_max_failures() {
+        let mut endpoints = Endpoints::new(["a", "b"])
+            .with_max_failures(0)
+            .with_ejection_attempts(10);
+
+        // Failures never eject an endpoint, so the rotation continues.
+        assert_eq!(vec!["a", "b", "a", "b"], picks(&mut endpoints, 4));
+        assert!(!endpoints.is_ejected(0));
+        assert!(!endpoints.is_ejected(1));
+    }
+
+    #[test]
+    fn test_all_ejected() {
+        let mut endpoints = Endpoints::new(["a"]).with_max_failures(1);
+        endpoints.report(false);
+        assert_eq!(&"a", endpoints.current());
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use core::future::ready;
+    use core::time::Duration;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use tokio::test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::ConstantBuilder;
+    use crate::RetryableWithContext;
+
+    #[test]
+    async fn test_rotate() {
+        let endpoints = Endpoints::new([1, 2, 3]);
+
+        let (endpoints, result) = Endpoints::rotate(|endpoint: i32| {
+            ready(if endpoint == 3 {
+                Ok(endpoint)
+            } else {
+                Err(endpoint)
+            })
+        })
+        .retry(ConstantBuilder::default())
+        .sleep(|_: Duration| ready(()))
+        .context(endpoints)
+        .await;
+
+        assert_eq!(Ok(3), result);
+        assert_eq!(&3, endpoints.current());
+    }
+}
@@ -0,0 +1,203 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::ready;
+use core::task::Context;
+use core::task::Poll;
+
+/// Failover runs alternate operations after the previous ones have failed.
+///
+/// Every tier is usually a [`Retry`](crate::Retry) with its own backoff, sleeper and
+/// notify, but any future returning a `Result` can be used, like a plain cache read.
+/// Tiers are only polled after all previous tiers have failed.
+///
+/// The output contains the value of the first success together with the index
+/// of the tier that served it, where `0` is the primary operation. If all tiers
+/// fail, the error of the last tier is returned.
+///
+/// Failover is created by [`Retry::failover`](crate::Retry::failover), more
+/// tiers can be appended with [`Failover::failover`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use backon::ConstantBuilder;
+/// use backon::ExponentialBuilder;
+/// use backon::Retryable;
+///
+/// async fn fetch(region: &str) -> Result<String> {
+///     Ok(format!("hello from {region}"))
+/// }
+///
+/// async fn read_cache() -> Result<String> {
+///     Ok("hello from cache".to_string())
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<()> {
+///     let (content, tier) = (|| fetch("us-east-1"))
+///         .retry(ExponentialBuilder::default())
+///         .failover((|| fetch("eu-west-1")).retry(ConstantBuilder::default()))
+///         .failover(read_cache())
+///         .await?;
+///     println!("fetch succeeded from tier {tier}: {content}");
+///
+///     Ok(())
+/// }
+/// ```
+pub struct Failover<P, S> {
+    primary: P,
+    secondary: S,
+    /// The index of the secondary tier, which is also the number of tiers in `primary`.
+    secondary_tier: usize,
+    primary_failed: bool,
+}
+
+impl<P, S> Failover<P, S> {
+    /// Append another tier, which runs after all current tiers have failed.
+    pub fn failover<N>(self, next: N) -> Failover<Self, N> {
+        let secondary_tier = self.secondary_tier + 1;
+        Failover {
+            primary: self,
+            secondary: next,
+            secondary_tier,
+            primary_failed: false,
+        }
+    }
+}
+
+impl<F, S> Failover<FailoverPrimary<F>, S> {
+    pub(crate) fn new(primary: F, secondary: S) -> Self {
+        Failover {
+            primary: FailoverPrimary(primary),
+            secondary,
+            secondary_tier: 1,
+            primary_failed: false,
+        }
+    }
+}
+
+impl<T, E, P, S> Future for Failover<P, S>
+where
+    P: Future<Output = Result<(T, usize), E>>,
+    S: Future<Output = Result<T, E>>,
+{
+    type Output = Result<(T, usize), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: This is safe because we don't move the `Failover` struct itself,
+        // only its internal state.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        if !this.primary_failed {
+            // Safety: This is safe because we don't move the `Failover` struct and this fut.
+            //
+            // We do the exactly same thing like `pin_project` but without depending on it directly.
+            let primary = unsafe { Pin::new_unchecked(&mut this.primary) };
+            match ready!(primary.poll(cx)) {
+                Ok(v) => return Poll::Ready(Ok(v)),
+                // The error of the last tier is returned instead.
+                Err(_) => this.primary_failed = true,
+            }
+        }
+
+        // Safety: This is safe because we don't move the `Failover` struct and this fut.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let secondary = unsafe { Pin::new_unchecked(&mut this.secondary) };
+        let v = ready!(secondary.poll(cx))?;
+        Poll::Ready(Ok((v, this.secondary_tier)))
+    }
+}
+
+/// FailoverPrimary is the first tier of a [`Failover`], which is always served as tier `0`.
+pub struct FailoverPrimary<F>(F);
+
+impl<T, E, F> Future for FailoverPrimary<F>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    type Output = Result<(T, usize), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: This is safe because we don't move the inner future.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let fut = unsafe { self.map_unchecked_mut(|v| &mut v.0) };
+        let v = ready!(fut.poll(cx))?;
+        Poll::Ready(Ok((v, 0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::string::String;
+    use alloc::string::ToString;
+    use core::future::ready;
+    use core::time::Duration;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use tokio::test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use crate::ConstantBuilder;
+    use crate::Retryable;
+
+    fn no_sleep(_: Duration) -> core::future::Ready<()> {
+        ready(())
+    }
+
+    #[test]
+    async fn test_failover_primary() {
+        let result = (|| ready(Ok::<_, String>("primary")))
+            .retry(ConstantBuilder::default())
+            .sleep(no_sleep)
+            .failover(ready(Ok("secondary")))
+            .await;
+
+        assert_eq!(Ok(("primary", 0)), result);
+    }
+
+    #[test]
+    async fn test_failover_to_last_tier() {
+        let mut primary_attempts = 0;
+        let mut secondary_attempts = 0;
+
+        let result = (|| {
+            primary_attempts += 1;
+            ready(Err::<&str, _>("primary failed".to_string()))
+        })
+        .retry(ConstantBuilder::default().with_max_times(2))
+        .sleep(no_sleep)
+        .failover(
+            (|| {
+                secondary_attempts += 1;
+                ready(Err("secondary failed".to_string()))
+            })
+            .retry(ConstantBuilder::default().with_max_times(1))
+            .sleep(no_sleep),
+        )
+        .failover(ready(Ok("cache")))
+        .await;
+
+        assert_eq!(Ok(("cache", 2)), result);
+        assert_eq!(3, primary_attempts);
+        assert_eq!(2, secondary_attempts);
+    }
+
+    #[test]
+    async fn test_failover_all_failed() {
+        let result = (|| ready(Err::<(), _>("primary failed")))
+            .retry(ConstantBuilder::default())
+            .sleep(no_sleep)
+            .failover(ready(Err("secondary failed")))
+            .await;
+
+        assert_eq!(Err("secondary failed"), result);
+    }
+}
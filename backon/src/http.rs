@@ -0,0 +1,243 @@
+//! Helpers for extracting retry delays from HTTP responses.
+//!
+//! Servers tell clients how long to wait before retrying through a handful of
+//! headers. The helpers in this module turn them into an `Option<Duration>`
+//! that can be used from [`Retry::adjust`][crate::Retry::adjust].
+//!
+//! The following headers are recognized, in order of precedence:
+//!
+//! - `Retry-After`: either delta-seconds (`120`) or an HTTP-date
+//!   (`Wed, 21 Oct 2015 07:28:00 GMT`).
+//! - `RateLimit-Reset`: delta-seconds until the quota resets.
+//! - `X-RateLimit-Reset`: delta-seconds or a unix timestamp in seconds, which
+//!   is what most public APIs send.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use anyhow::Result;
+//! use backon::http::retry_after;
+//! use backon::ExponentialBuilder;
+//! use backon::Retryable;
+//! use http::HeaderMap;
+//!
+//! #[derive(Debug)]
+//! struct HttpError {
+//!     headers: HeaderMap,
+//! }
+//!
+//! impl std::fmt::Display for HttpError {
+//!     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+//!         write!(f, "http error")
+//!     }
+//! }
+//!
+//! impl std::error::Error for HttpError {}
+//!
+//! async fn fetch() -> Result<String> {
+//!     Err(HttpError {
+//!         headers: HeaderMap::new(),
+//!     }
+//!     .into())
+//! }
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() -> Result<()> {
+//!     let content = fetch
+//!         .retry(ExponentialBuilder::default())
+//!         .adjust(|err, dur| match err.downcast_ref::<HttpError>() {
+//!             // Honor the server's delay, but stop once the backoff is exhausted.
+//!             Some(v) => dur.map(|dur| retry_after(&v.headers).unwrap_or(dur)),
+//!             None => dur,
+//!         })
+//!         .await?;
+//!     println!("fetch succeeded: {}", content);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use core::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Response;
+
+/// The `RateLimit-Reset` header from the IETF rate limit headers draft.
+const RATELIMIT_RESET: &str = "ratelimit-reset";
+/// The de facto `X-RateLimit-Reset` header sent by many public APIs.
+const X_RATELIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// `X-RateLimit-Reset` values above this are treated as unix timestamps
+/// instead of delta-seconds.
+///
+/// The value is roughly one year in seconds: no server asks clients to wait
+/// that long, and every meaningful timestamp is far larger.
+const UNIX_TIMESTAMP_THRESHOLD: u64 = 365 * 24 * 60 * 60;
+
+/// Extract the delay requested by the server from the given headers.
+///
+/// `Retry-After` takes precedence over `RateLimit-Reset`, which takes precedence
+/// over `X-RateLimit-Reset`. Headers that cannot be parsed are ignored. Dates in
+/// the past yield [`Duration::ZERO`].
+///
+/// Returns `None` if no recognized header is present.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(http::header::RETRY_AFTER)
+        .and_then(parse_retry_after)
+        .or_else(|| headers.get(RATELIMIT_RESET).and_then(parse_delta_seconds))
+        .or_else(|| {
+            headers
+                .get(X_RATELIMIT_RESET)
+                .and_then(parse_x_ratelimit_reset)
+        })
+}
+
+/// Extract the delay requested by the server from the given response.
+///
+/// This is a shorthand for [`retry_after`] over [`Response::headers`].
+pub fn retry_after_from_response<T>(resp: &Response<T>) -> Option<Duration> {
+    retry_after(resp.headers())
+}
+
+/// Parse a `Retry-After` value, which is either delta-seconds or an HTTP-date.
+fn parse_retry_after(value: &HeaderValue) -> Option<Duration> {
+    if let Some(dur) = parse_delta_seconds(value) {
+        return Some(dur);
+    }
+
+    let value = value.to_str().ok()?;
+    let at = httpdate::parse_http_date(value.trim()).ok()?;
+    Some(
+        at.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Parse an `X-RateLimit-Reset` value, which is either delta-seconds or a unix timestamp.
+fn parse_x_ratelimit_reset(value: &HeaderValue) -> Option<Duration> {
+    let secs = parse_seconds(value)?;
+    if secs <= UNIX_TIMESTAMP_THRESHOLD {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = UNIX_EPOCH.checked_add(Duration::from_secs(secs))?;
+    Some(
+        at.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+fn parse_delta_seconds(value: &HeaderValue) -> Option<Duration> {
+    parse_seconds(value).map(Duration::from_secs)
+}
+
+fn parse_seconds(value: &HeaderValue) -> Option<u64> {
+    value.to_str().ok()?.trim().parse::<u64>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::string::ToString;
+    use std::time::SystemTime;
+    use std::time::UNIX_EPOCH;
+
+    use http::HeaderMap;
+    use http::HeaderValue;
+    use http::Response;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (k, v) in pairs {
+            headers.insert(*k, HeaderValue::from_str(v).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_retry_after_none() {
+        assert_eq!(None, retry_after(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_retry_after_delta_seconds() {
+        let h = headers(&[("retry-after", "120")]);
+        assert_eq!(Some(Duration::from_secs(120)), retry_after(&h));
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let at = SystemTime::now() + Duration::from_secs(120);
+        let h = headers(&[("retry-after", &httpdate::fmt_http_date(at))]);
+
+        let dur = retry_after(&h).expect("must be parsed");
+        assert!(dur <= Duration::from_secs(120), "current: {dur:?}");
+        assert!(dur >= Duration::from_secs(100), "current: {dur:?}");
+    }
+
+    #[test]
+    fn test_retry_after_http_date_in_past() {
+        let h = headers(&[("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")]);
+        assert_eq!(Some(Duration::ZERO), retry_after(&h));
+    }
+
+    #[test]
+    fn test_retry_after_invalid() {
+        let h = headers(&[("retry-after", "soon")]);
+        assert_eq!(None, retry_after(&h));
+    }
+
+    #[test]
+    fn test_ratelimit_reset() {
+        let h = headers(&[("ratelimit-reset", "30")]);
+        assert_eq!(Some(Duration::from_secs(30)), retry_after(&h));
+    }
+
+    #[test]
+    fn test_x_ratelimit_reset_delta_seconds() {
+        let h = headers(&[("x-ratelimit-reset", "15")]);
+        assert_eq!(Some(Duration::from_secs(15)), retry_after(&h));
+    }
+
+    #[test]
+    fn test_x_ratelimit_reset_timestamp() {
+        let at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(60);
+        let h = headers(&[("x-ratelimit-reset", &at.as_secs().to_string())]);
+
+        let dur = retry_after(&h).expect("must be parsed");
+        assert!(dur <= Duration::from_secs(60), "current: {dur:?}");
+        assert!(dur >= Duration::from_secs(50), "current: {dur:?}");
+    }
+
+    #[test]
+    fn test_retry_after_precedence() {
+        let h = headers(&[
+            ("x-ratelimit-reset", "15"),
+            ("ratelimit-reset", "30"),
+            ("retry-after", "5"),
+        ]);
+        assert_eq!(Some(Duration::from_secs(5)), retry_after(&h));
+
+        let h = headers(&[("x-ratelimit-reset", "15"), ("ratelimit-reset", "30")]);
+        assert_eq!(Some(Duration::from_secs(30)), retry_after(&h));
+    }
+
+    #[test]
+    fn test_retry_after_from_response() {
+        let resp = Response::builder()
+            .status(429)
+            .header("retry-after", "7")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            Some(Duration::from_secs(7)),
+            retry_after_from_response(&resp)
+        );
+    }
+}
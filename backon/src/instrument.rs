@@ -0,0 +1,665 @@
+//! Instrumentation shared by all retry types.
+//!
+//! Every retry holds an [`Instrument`], which counts attempts but does nothing
+//! else until it is enabled by `trace(name)` or `metrics(policy)`. Without the
+//! `tracing` and `metrics` features, all of its other methods are no-ops.
+
+#[cfg(feature = "tracing")]
+use core::fmt::Debug;
+#[cfg(feature = "tracing")]
+use core::fmt::Display;
+use core::marker::PhantomData;
+use core::time::Duration;
+
+/// The reason why a retry gives up and returns the last error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GiveUp {
+    /// The error is not retryable.
+    NotRetryable,
+    /// The backoff doesn't allow another attempt.
+    Exhausted,
+    /// The retry has been cancelled.
+    Cancelled,
+    /// The sleeper has been interrupted.
+    Interrupted,
+    /// The retry limiter doesn't allow another retry.
+    Limited,
+}
+
+impl GiveUp {
+    #[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(dead_code))]
+    fn as_str(self) -> &'static str {
+        match self {
+            GiveUp::NotRetryable => "not_retryable",
+            GiveUp::Exhausted => "exhausted",
+            GiveUp::Cancelled => "cancelled",
+            GiveUp::Interrupted => "interrupted",
+            GiveUp::Limited => "limited",
+        }
+    }
+}
+
+/// A span guard returned by [`Instrument::enter`] and [`Instrument::enter_attempt`].
+#[cfg(feature = "tracing")]
+pub(crate) type Entered = Option<tracing::span::EnteredSpan>;
+
+/// A span guard returned by [`Instrument::enter`] and [`Instrument::enter_attempt`].
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+pub(crate) struct Instrument<E> {
+    /// The number of attempts started so far.
+    attempt: usize,
+    #[cfg(feature = "tracing")]
+    tracer: Option<Tracer<E>>,
+    /// The policy name used to label metrics.
+    #[cfg(feature = "metrics")]
+    policy: Option<metrics::SharedString>,
+    /// The delay of the scheduled retry, recorded once its attempt starts.
+    #[cfg(feature = "metrics")]
+    scheduled: Option<Duration>,
+    _error: PhantomData<fn(&E)>,
+}
+
+// Errors are only formatted by tracing.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+impl<E> Instrument<E> {
+    pub(crate) fn new() -> Self {
+        Instrument {
+            attempt: 0,
+            #[cfg(feature = "tracing")]
+            tracer: None,
+            #[cfg(feature = "metrics")]
+            policy: None,
+            #[cfg(feature = "metrics")]
+            scheduled: None,
+            _error: PhantomData,
+        }
+    }
+
+    /// Emit tracing spans and events named by `name`.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn with_tracing(mut self, name: &'static str) -> Self
+    where
+        E: Display + Debug,
+    {
+        self.tracer = Some(Tracer::new(name));
+        self
+    }
+
+    /// Record metrics labeled by `policy`.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_metrics(mut self, policy: metrics::SharedString) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Enter the span of the retry loop.
+    pub(crate) fn enter(&mut self) -> Entered {
+        #[cfg(feature = "tracing")]
+        return self.tracer.as_mut().map(Tracer::enter);
+        #[cfg(not(feature = "tracing"))]
+        Entered
+    }
+
+    /// Enter the span of the running attempt.
+    pub(crate) fn enter_attempt(&self) -> Entered {
+        #[cfg(feature = "tracing")]
+        return self.tracer.as_ref().map(Tracer::enter_attempt);
+        #[cfg(not(feature = "tracing"))]
+        Entered
+    }
+
+    /// Returns the number of attempts started so far.
+    pub(crate) fn attempt(&self) -> usize {
+        self.attempt
+    }
+
+    pub(crate) fn attempt_start(&mut self) {
+        self.attempt += 1;
+        #[cfg(feature = "tracing")]
+        if let Some(tracer) = &mut self.tracer {
+            tracer.attempt_start(self.attempt);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(policy) = &self.policy {
+            // Retries are counted once they run, a retry cancelled while sleeping is not.
+            if let Some(dur) = self.scheduled.take() {
+                metrics::counter!("backon_retries_total", "policy" => policy.clone()).increment(1);
+                metrics::histogram!("backon_sleep_seconds", "policy" => policy.clone())
+                    .record(dur.as_secs_f64());
+            }
+            metrics::counter!("backon_attempts_total", "policy" => policy.clone()).increment(1);
+        }
+    }
+
+    pub(crate) fn success(&mut self) {
+        #[cfg(feature = "tracing")]
+        if let Some(tracer) = &mut self.tracer {
+            tracer.success(self.attempt);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(policy) = &self.policy {
+            if self.attempt > 1 {
+                metrics::counter!("backon_successes_after_retry_total", "policy" => policy.clone())
+                    .increment(1);
+            }
+        }
+    }
+
+    pub(crate) fn retry(&mut self, err: &E, dur: Duration) {
+        #[cfg(feature = "tracing")]
+        if let Some(tracer) = &mut self.tracer {
+            tracer.retry(self.attempt, err, dur);
+        }
+        #[cfg(feature = "metrics")]
+        if self.policy.is_some() {
+            self.scheduled = Some(dur);
+        }
+    }
+
+    pub(crate) fn give_up(&mut self, err: &E, reason: GiveUp) {
+        #[cfg(feature = "tracing")]
+        if let Some(tracer) = &mut self.tracer {
+            tracer.give_up(self.attempt, err, reason);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(policy) = &self.policy {
+            metrics::counter!(
+                "backon_give_ups_total",
+                "policy" => policy.clone(),
+                "reason" => reason.as_str()
+            )
+            .increment(1);
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+struct Tracer<E> {
+    name: &'static str,
+    fmt: fn(&E) -> (&dyn Display, &dyn Debug),
+    /// The span of the whole retry loop, created with the first attempt.
+    span: tracing::Span,
+    /// The span of the running attempt.
+    attempt_span: tracing::Span,
+}
+
+#[cfg(feature = "tracing")]
+fn fmt_error<E: Display + Debug>(err: &E) -> (&dyn Display, &dyn Debug) {
+    (err, err)
+}
+
+#[cfg(feature = "tracing")]
+impl<E> Tracer<E> {
+    fn new(name: &'static str) -> Self
+    where
+        E: Display + Debug,
+    {
+        Tracer {
+            name,
+            fmt: fmt_error::<E>,
+            span: tracing::Span::none(),
+            attempt_span: tracing::Span::none(),
+        }
+    }
+
+    fn enter(&mut self) -> tracing::span::EnteredSpan {
+        if self.span.is_none() {
+            self.span =
+                tracing::info_span!("retry", name = self.name, attempts = tracing::field::Empty);
+        }
+        self.span.clone().entered()
+    }
+
+    fn enter_attempt(&self) -> tracing::span::EnteredSpan {
+        self.attempt_span.clone().entered()
+    }
+
+    fn attempt_start(&mut self, attempt: usize) {
+        self.span.record("attempts", attempt);
+        self.attempt_span = tracing::info_span!(
+            parent: &self.span,
+            "attempt",
+            name = self.name,
+            attempt = attempt
+        );
+    }
+
+    fn success(&mut self, attempt: usize) {
+        tracing::debug!(
+            parent: &self.attempt_span,
+            attempt = attempt,
+            "attempt succeeded"
+        );
+        self.attempt_span = tracing::Span::none();
+    }
+
+    fn retry(&mut self, attempt: usize, err: &E, dur: Duration) {
+        let (err_display, err_debug) = (self.fmt)(err);
+        tracing::warn!(
+            parent: &self.attempt_span,
+            attempt = attempt,
+            delay = ?dur,
+            error = %err_display,
+            error.debug = ?err_debug,
+            "attempt failed, retrying"
+        );
+        self.attempt_span = tracing::Span::none();
+    }
+
+    fn give_up(&mut self, attempt: usize, err: &E, reason: GiveUp) {
+        let (err_display, err_debug) = (self.fmt)(err);
+        let parent = if self.attempt_span.is_none() {
+            &self.span
+        } else {
+            &self.attempt_span
+        };
+        tracing::error!(
+            parent: parent,
+            attempt = attempt,
+            reason = reason.as_str(),
+            error = %err_display,
+            error.debug = ?err_debug,
+            "giving up"
+        );
+        self.attempt_span = tracing::Span::none();
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(not(target_arch = "wasm32"), feature = "tracing"))]
+mod tracing_tests {
+    extern crate alloc;
+
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::fmt::Debug;
+    use core::sync::atomic::AtomicU64;
+    use core::sync::atomic::Ordering;
+
+    use spin::Mutex;
+    use tracing::field::Field;
+    use tracing::field::Visit;
+    use tracing::span;
+    use tracing::Event;
+    use tracing::Metadata;
+    use tracing::Subscriber;
+
+    use crate::BlockingRetryable;
+    use crate::ConstantBuilder;
+    use crate::Retryable;
+
+    /// Records spans and events as `name field=value ...` lines.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        lines: Arc<Mutex<Vec<String>>>,
+        next_id: Arc<AtomicU64>,
+    }
+
+    struct Line(String);
+
+    impl Visit for Line {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0 += &format!(" {}={:?}", field.name(), value);
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0 += &format!(" {}={}", field.name(), value);
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut line = Line(format!("span {}", span.metadata().name()));
+            span.record(&mut line);
+            self.lines.lock().push(line.0);
+            span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut line = Line(format!("event {}", event.metadata().level()));
+            event.record(&mut line);
+            self.lines.lock().push(line.0);
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_trace_blocking_retry() {
+        let recorder = Recorder::default();
+        let mut attempts = 0;
+
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            (|| {
+                attempts += 1;
+                if attempts < 2 {
+                    Err("transient")
+                } else {
+                    Err("permanent")
+                }
+            })
+            .retry(ConstantBuilder::default().with_delay(core::time::Duration::ZERO))
+            .when(|e| *e == "transient")
+            .trace("fetch")
+            .call()
+        });
+
+        assert_eq!(Err::<(), _>("permanent"), result);
+        assert_eq!(
+            vec![
+                "span retry name=fetch",
+                "span attempt name=fetch attempt=1",
+                "event WARN message=attempt failed, retrying attempt=1 delay=0ns error=transient error.debug=\"transient\"",
+                "span attempt name=fetch attempt=2",
+                "event ERROR message=giving up attempt=2 reason=not_retryable error=permanent error.debug=\"permanent\"",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[test]
+    fn test_trace_success() {
+        let recorder = Recorder::default();
+
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            (|| Ok::<_, &str>(42))
+                .retry(ConstantBuilder::default())
+                .trace("fetch")
+                .call()
+        });
+
+        assert_eq!(Ok(42), result);
+        assert_eq!(
+            vec![
+                "span retry name=fetch",
+                "span attempt name=fetch attempt=1",
+                "event DEBUG message=attempt succeeded attempt=1",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_retry() {
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let result = (|| core::future::ready(Err::<(), _>("error")))
+            .retry(ConstantBuilder::default().with_max_times(1))
+            .sleep(|_| core::future::ready(()))
+            .trace("fetch")
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            vec![
+                "span retry name=fetch",
+                "span attempt name=fetch attempt=1",
+                "event WARN message=attempt failed, retrying attempt=1 delay=1s error=error error.debug=\"error\"",
+                "span attempt name=fetch attempt=2",
+                "event ERROR message=giving up attempt=2 reason=exhausted error=error error.debug=\"error\"",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[test]
+    fn test_trace_disabled() {
+        let recorder = Recorder::default();
+
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            (|| Err::<(), _>("error"))
+                .retry(ConstantBuilder::default().with_delay(core::time::Duration::ZERO))
+                .call()
+        });
+
+        assert!(result.is_err());
+        assert!(recorder.lines.lock().is_empty());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(not(target_arch = "wasm32"), feature = "metrics"))]
+mod metrics_tests {
+    extern crate alloc;
+
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::sync::atomic::AtomicBool;
+    use core::sync::atomic::Ordering;
+
+    use metrics::Counter;
+    use metrics::CounterFn;
+    use metrics::Gauge;
+    use metrics::Histogram;
+    use metrics::HistogramFn;
+    use metrics::Key;
+    use metrics::KeyName;
+    use metrics::Metadata;
+    use metrics::Recorder;
+    use metrics::SharedString;
+    use metrics::Unit;
+    use spin::Mutex;
+
+    use super::*;
+    use crate::BlockingRetryable;
+    use crate::BlockingRetryableWithContext;
+    use crate::BlockingSleeper;
+    use crate::ConstantBuilder;
+    use crate::RetryableWithContext;
+
+    /// Records every update as a `name{labels} value` line.
+    #[derive(Clone, Default)]
+    struct TestRecorder {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct Handle {
+        key: String,
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CounterFn for Handle {
+        fn increment(&self, value: u64) {
+            self.lines.lock().push(format!("{} +{}", self.key, value));
+        }
+
+        fn absolute(&self, _: u64) {
+            unreachable!("counters are only incremented")
+        }
+    }
+
+    impl HistogramFn for Handle {
+        fn record(&self, value: f64) {
+            self.lines.lock().push(format!("{} {}", self.key, value));
+        }
+    }
+
+    impl TestRecorder {
+        fn handle(&self, key: &Key) -> Arc<Handle> {
+            let labels: Vec<_> = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            Arc::new(Handle {
+                key: format!("{}{{{}}}", key.name(), labels.join(",")),
+                lines: self.lines.clone(),
+            })
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.handle(key))
+        }
+    }
+
+    #[test]
+    fn test_metrics_success_after_retry() {
+        let recorder = TestRecorder::default();
+        let mut attempts = 0;
+
+        let result = metrics::with_local_recorder(&recorder, || {
+            (|| {
+                attempts += 1;
+                if attempts < 2 {
+                    Err("transient")
+                } else {
+                    Ok(attempts)
+                }
+            })
+            .retry(ConstantBuilder::default().with_delay(Duration::ZERO))
+            .metrics("fetch")
+            .call()
+        });
+
+        assert_eq!(Ok(2), result);
+        assert_eq!(
+            vec![
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_retries_total{policy=fetch} +1",
+                "backon_sleep_seconds{policy=fetch} 0",
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_successes_after_retry_total{policy=fetch} +1",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[test]
+    fn test_metrics_give_up() {
+        let recorder = TestRecorder::default();
+
+        let result = metrics::with_local_recorder(&recorder, || {
+            (|| Err::<(), _>("permanent"))
+                .retry(ConstantBuilder::default())
+                .when(|_| false)
+                .metrics(String::from("fetch"))
+                .call()
+        });
+
+        assert!(result.is_err());
+        assert_eq!(
+            vec![
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_give_ups_total{policy=fetch,reason=not_retryable} +1",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[test]
+    fn test_metrics_interrupted() {
+        struct Interrupting(AtomicBool);
+
+        impl BlockingSleeper for Interrupting {
+            fn sleep(&self, _: Duration) {
+                self.0.store(true, Ordering::Relaxed);
+            }
+
+            fn is_interrupted(&self) -> bool {
+                self.0.load(Ordering::Relaxed)
+            }
+        }
+
+        let recorder = TestRecorder::default();
+
+        let result = metrics::with_local_recorder(&recorder, || {
+            (|| Err::<(), _>("transient"))
+                .retry(ConstantBuilder::default().with_delay(Duration::ZERO))
+                .sleep(Interrupting(AtomicBool::new(false)))
+                .metrics("fetch")
+                .call()
+        });
+
+        // The retry is interrupted while sleeping, before its attempt starts.
+        assert!(result.is_err());
+        assert_eq!(
+            vec![
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_give_ups_total{policy=fetch,reason=interrupted} +1",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_retry_with_context() {
+        let recorder = TestRecorder::default();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let (_, result) = (|ctx: ()| core::future::ready((ctx, Err::<(), _>("error"))))
+            .retry(ConstantBuilder::default().with_max_times(1))
+            .sleep(|_| core::future::ready(()))
+            .context(())
+            .metrics("fetch")
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            vec![
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_retries_total{policy=fetch} +1",
+                "backon_sleep_seconds{policy=fetch} 1",
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_give_ups_total{policy=fetch,reason=exhausted} +1",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+
+    #[test]
+    fn test_metrics_blocking_retry_with_context() {
+        let recorder = TestRecorder::default();
+
+        let (_, result) = metrics::with_local_recorder(&recorder, || {
+            (|ctx: ()| (ctx, Err::<(), _>("permanent")))
+                .retry(ConstantBuilder::default())
+                .context(())
+                .when(|_| false)
+                .metrics("fetch")
+                .call()
+        });
+
+        assert!(result.is_err());
+        assert_eq!(
+            vec![
+                "backon_attempts_total{policy=fetch} +1",
+                "backon_give_ups_total{policy=fetch,reason=not_retryable} +1",
+            ],
+            *recorder.lines.lock()
+        );
+    }
+}
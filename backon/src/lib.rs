@@ -28,6 +28,8 @@
 //! - [`ConstantBuilder`]: backoff with a constant delay, limited to a specific number of attempts.
 //! - [`ExponentialBuilder`]: backoff with an exponential delay, also supports jitter.
 //! - [`FibonacciBuilder`]: backoff with a fibonacci delay, also supports jitter.
+//! - [`CurveBuilder`]: backoff with a delay following any function of the attempt, like [`Polynomial`], also supports jitter.
+//! - [`SequenceBuilder`]: backoff replaying a list of delays, also supports jitter.
 //!
 //! # Sleep
 //!
@@ -46,8 +48,10 @@
 //! | [`TokioSleeper`]        | tokio-sleep         | non-wasm32  |  Yes          |
 //! | [`GlooTimersSleep`]     | gloo-timers-sleep   |   wasm32    |  Yes          |
 //! | [`FuturesTimerSleeper`] | futures-timer-sleep |wasm/non-wasm|  Yes          |
+//! | [`SmolSleeper`]         | async-io-sleep      | non-wasm32  |  Yes          |
 //! | [`EmbassySleep`]        | embassy-sleep       |   no_std    |  Yes          |
 //! | [`StdSleeper`]          | std-blocking-sleep  |    std      |  No           |
+//! | [`CondvarSleeper`]      | std-blocking-sleep  |    std      |  No           |
 //!
 //! ## Custom Sleeper
 //!
@@ -211,7 +215,7 @@
 #![deny(unused_qualifications)]
 #![no_std]
 
-#[cfg(feature = "std-blocking-sleep")]
+#[cfg(any(feature = "std", feature = "std-blocking-sleep"))]
 extern crate std;
 
 mod backoff;
@@ -221,10 +225,40 @@ mod retry;
 pub use retry::Retry;
 pub use retry::Retryable;
 
+mod retryable_error;
+pub use retryable_error::RetryableError;
+
+mod failover;
+pub use failover::Failover;
+pub use failover::FailoverPrimary;
+
+#[cfg(feature = "std")]
+mod endpoints;
+#[cfg(feature = "std")]
+pub use endpoints::Endpoints;
+#[cfg(feature = "std")]
+pub use endpoints::Rotate;
+
 mod retry_with_context;
 pub use retry_with_context::RetryWithContext;
 pub use retry_with_context::RetryableWithContext;
 
+#[cfg(feature = "stream")]
+mod retry_stream;
+#[cfg(feature = "stream")]
+pub use retry_stream::RetryStream;
+#[cfg(feature = "stream")]
+pub use retry_stream::StreamRetryable;
+
+mod instrument;
+
+mod observer;
+pub use observer::RetryObserver;
+
+mod limiter;
+#[cfg(feature = "std")]
+pub use limiter::RetryLimiter;
+
 mod sleep;
 pub use sleep::DefaultSleeper;
 #[cfg(feature = "futures-timer-sleep")]
@@ -232,6 +266,10 @@ pub use sleep::FuturesTimerSleeper;
 #[cfg(all(target_arch = "wasm32", feature = "gloo-timers-sleep"))]
 pub use sleep::GlooTimersSleep;
 pub use sleep::Sleeper;
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+pub use sleep::SmolSleeper;
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+pub use sleep::SmolTimer;
 #[cfg(all(not(target_arch = "wasm32"), feature = "tokio-sleep"))]
 pub use sleep::TokioSleeper;
 
@@ -245,6 +283,8 @@ pub use blocking_retry_with_context::BlockingRetryableWithContext;
 
 mod blocking_sleep;
 pub use blocking_sleep::BlockingSleeper;
+#[cfg(feature = "std-blocking-sleep")]
+pub use blocking_sleep::CondvarSleeper;
 pub use blocking_sleep::DefaultBlockingSleeper;
 #[cfg(feature = "std-blocking-sleep")]
 pub use blocking_sleep::StdSleeper;
@@ -254,5 +294,32 @@ mod embassy_timer_sleep;
 #[cfg(feature = "embassy-sleep")]
 pub use embassy_timer_sleep::EmbassySleeper;
 
+#[cfg(feature = "std")]
+pub mod classify;
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "macros")]
+pub use backon_macros::retry;
+#[cfg(feature = "macros")]
+pub use backon_macros::RetryableError;
+
+#[doc(hidden)]
+pub mod __private;
+
+#[cfg(feature = "reqwest-middleware")]
+mod retry_middleware;
+#[cfg(feature = "reqwest-middleware")]
+pub use retry_middleware::RetryMiddleware;
+
+#[cfg(feature = "tower")]
+mod tower;
+#[cfg(feature = "tower")]
+pub use tower::BackonRetryFuture;
+#[cfg(feature = "tower")]
+pub use tower::BackonRetryLayer;
+#[cfg(feature = "tower")]
+pub use tower::BackonRetryService;
+
 #[cfg(docsrs)]
 pub mod docs;
@@ -0,0 +1,473 @@
+//! Concurrency limit on retries shared by [`Retry`](crate::Retry) and
+//! [`RetryWithContext`](crate::RetryWithContext).
+//!
+//! Every async retry holds a [`Limit`], which admits every retry until it is
+//! given a [`RetryLimiter`] by `limit(limiter)`. Without the `std` feature,
+//! [`RetryLimiter`] is not available and [`Limit`] never limits anything.
+
+use core::task::Context;
+use core::task::Poll;
+#[cfg(feature = "std")]
+use core::task::Waker;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(feature = "std")]
+use std::sync::PoisonError;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// RetryLimiter caps how many retries can be retrying at the same time.
+///
+/// A retry is retrying from the moment its first attempt fails and another attempt
+/// is scheduled, until it returns. First attempts are never limited. Once `max`
+/// retries are retrying, the others wait before their next attempt until one of
+/// them returns, or give up with their last error if
+/// [`with_give_up`](RetryLimiter::with_give_up) is set.
+///
+/// This keeps an outage from turning into a retry storm that prevents the backend
+/// from recovering. Clones of a `RetryLimiter` share the same limit, so one limiter
+/// can be passed to many [`Retry`](crate::Retry) and
+/// [`RetryWithContext`](crate::RetryWithContext) with `limit`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use backon::ExponentialBuilder;
+/// use backon::RetryLimiter;
+/// use backon::Retryable;
+///
+/// async fn fetch() -> Result<String> {
+///     Ok("hello, world!".to_string())
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<()> {
+///     // At most 4 requests can be retrying at once, the others fail fast.
+///     let limiter = RetryLimiter::new(4).with_give_up();
+///
+///     let tasks: Vec<_> = (0..16)
+///         .map(|_| {
+///             tokio::spawn(
+///                 fetch
+///                     .retry(ExponentialBuilder::default())
+///                     .limit(limiter.clone()),
+///             )
+///         })
+///         .collect();
+///     for task in tasks {
+///         println!("fetch succeeded: {}", task.await??);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct RetryLimiter {
+    semaphore: Arc<Semaphore>,
+    give_up: bool,
+}
+
+#[cfg(feature = "std")]
+impl RetryLimiter {
+    /// Create a new `RetryLimiter` that allows at most `max` retries to be retrying at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero, since no retry could ever get a permit.
+    pub fn new(max: usize) -> Self {
+        assert!(max > 0, "max of retry limiter must be greater than 0");
+        Self {
+            semaphore: Arc::new(Semaphore {
+                max,
+                state: Mutex::new(SemaphoreState::default()),
+            }),
+            give_up: false,
+        }
+    }
+
+    /// Give up with the last error instead of waiting when the limit is reached.
+    ///
+    /// This only affects retries limited by this handle, other clones keep waiting
+    /// unless they are configured the same way.
+    pub fn with_give_up(mut self) -> Self {
+        self.give_up = true;
+        self
+    }
+
+    /// Returns the number of retries that are retrying now.
+    pub fn retrying(&self) -> usize {
+        self.semaphore.lock().retrying
+    }
+}
+
+/// Semaphore is a minimal async semaphore that hands permits to waiters in FIFO order.
+///
+/// A released permit goes straight to the first waiter, and only that waiter is woken
+/// up, so a release never wakes the whole queue.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Semaphore {
+    max: usize,
+    state: Mutex<SemaphoreState>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct SemaphoreState {
+    retrying: usize,
+    next_waiter: usize,
+    /// Waiters queued for a permit, in arrival order.
+    waiters: VecDeque<(usize, Waker)>,
+    /// Waiters that have been handed a permit but have not been polled yet.
+    granted: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl Semaphore {
+    fn lock(&self) -> std::sync::MutexGuard<'_, SemaphoreState> {
+        // The state is always consistent between statements, so it's fine to ignore poisoning.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn try_acquire(&self) -> bool {
+        let mut state = self.lock();
+        if state.retrying < self.max {
+            state.retrying += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Acquire a permit, queueing `waiter` to be handed one if there is none left.
+    fn poll_acquire(&self, waiter: &mut Option<usize>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.lock();
+        if let Some(id) = *waiter {
+            if let Some(idx) = state.granted.iter().position(|granted| *granted == id) {
+                state.granted.swap_remove(idx);
+                *waiter = None;
+                return Poll::Ready(());
+            }
+            if let Some((_, waker)) = state.waiters.iter_mut().find(|(w, _)| *w == id) {
+                waker.clone_from(cx.waker());
+            }
+            return Poll::Pending;
+        }
+
+        // Permits are handed over while anyone is queued, so a free permit means
+        // there is no one to overtake.
+        if state.retrying < self.max {
+            state.retrying += 1;
+            return Poll::Ready(());
+        }
+
+        let id = state.next_waiter;
+        state.next_waiter = state.next_waiter.wrapping_add(1);
+        state.waiters.push_back((id, cx.waker().clone()));
+        *waiter = Some(id);
+        Poll::Pending
+    }
+
+    fn release(&self) {
+        let mut state = self.lock();
+        // Hand the permit over to the first waiter instead of releasing it.
+        let Some((id, waker)) = state.waiters.pop_front() else {
+            state.retrying -= 1;
+            return;
+        };
+        state.granted.push(id);
+        drop(state);
+        waker.wake();
+    }
+
+    fn cancel(&self, id: usize) {
+        let mut state = self.lock();
+        if let Some(idx) = state.granted.iter().position(|granted| *granted == id) {
+            // The waiter has been handed a permit it will never use, pass it on.
+            state.granted.swap_remove(idx);
+            drop(state);
+            self.release();
+        } else {
+            state.waiters.retain(|(waiter, _)| *waiter != id);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+enum LimitState {
+    /// Not retrying yet, no permit is held.
+    #[default]
+    Idle,
+    /// Waiting for a permit before the next attempt.
+    Waiting(Option<usize>),
+    /// Holding a permit until the retry returns.
+    Acquired,
+}
+
+/// Limit applies the [`RetryLimiter`] of a retry, if any.
+#[derive(Debug, Default)]
+pub(crate) struct Limit {
+    #[cfg(feature = "std")]
+    limiter: Option<RetryLimiter>,
+    #[cfg(feature = "std")]
+    state: LimitState,
+}
+
+impl Limit {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn with_limiter(mut self, limiter: RetryLimiter) -> Self {
+        self.release();
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Admit the retry scheduled after a failed attempt.
+    ///
+    /// Returns `false` if the retry should give up instead.
+    pub(crate) fn admit(&mut self) -> bool {
+        #[cfg(feature = "std")]
+        if let Some(limiter) = &self.limiter {
+            if !matches!(self.state, LimitState::Idle) {
+                return true;
+            }
+            if limiter.semaphore.try_acquire() {
+                self.state = LimitState::Acquired;
+            } else if limiter.give_up {
+                return false;
+            } else {
+                self.state = LimitState::Waiting(None);
+            }
+        }
+        true
+    }
+
+    /// Wait until the next attempt is allowed to start.
+    pub(crate) fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        #[cfg(feature = "std")]
+        if let (Some(limiter), LimitState::Waiting(waiter)) = (&self.limiter, &mut self.state) {
+            core::task::ready!(limiter.semaphore.poll_acquire(waiter, cx));
+            self.state = LimitState::Acquired;
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = cx;
+        Poll::Ready(())
+    }
+
+    /// Leave the retrying state once the retry returns.
+    pub(crate) fn release(&mut self) {
+        #[cfg(feature = "std")]
+        if let Some(limiter) = &self.limiter {
+            match core::mem::take(&mut self.state) {
+                LimitState::Idle | LimitState::Waiting(None) => {}
+                LimitState::Waiting(Some(id)) => limiter.semaphore.cancel(id),
+                LimitState::Acquired => limiter.semaphore.release(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Limit {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+    use std::vec;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    #[test]
+    fn test_semaphore_wakes_first_waiter() {
+        let limiter = RetryLimiter::new(1);
+        let semaphore = &limiter.semaphore;
+        assert!(semaphore.try_acquire());
+
+        let wakers: Vec<_> = (0..3).map(|_| Arc::new(CountingWaker::default())).collect();
+        let mut waiters = [None; 3];
+        for (waker, waiter) in wakers.iter().zip(&mut waiters) {
+            let waker = Waker::from(waker.clone());
+            let mut cx = Context::from_waker(&waker);
+            assert!(semaphore.poll_acquire(waiter, &mut cx).is_pending());
+        }
+
+        // Only the first waiter is woken up and handed the permit.
+        semaphore.release();
+        assert_eq!(
+            vec![1, 0, 0],
+            wakers.iter().map(|w| w.count()).collect::<Vec<_>>()
+        );
+        assert_eq!(1, limiter.retrying());
+
+        // A waiter that gives up its permit passes it on to the next one.
+        semaphore.cancel(waiters[0].unwrap());
+        assert_eq!(
+            vec![1, 1, 0],
+            wakers.iter().map(|w| w.count()).collect::<Vec<_>>()
+        );
+
+        let waker = Waker::from(wakers[1].clone());
+        let mut cx = Context::from_waker(&waker);
+        assert!(semaphore.poll_acquire(&mut waiters[1], &mut cx).is_ready());
+        assert!(semaphore
+            .poll_acquire(&mut waiters[2], &mut cx)
+            .is_pending());
+        assert_eq!(1, limiter.retrying());
+    }
+
+    #[test]
+    #[should_panic(expected = "max of retry limiter must be greater than 0")]
+    fn test_limit_zero() {
+        let _ = RetryLimiter::new(0);
+    }
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl CountingWaker {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    impl std::task::Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod retry_tests {
+    use core::future::ready;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::time::Duration;
+    use std::boxed::Box;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use tokio::test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::ConstantBuilder;
+    use crate::Retryable;
+    use crate::RetryableWithContext;
+
+    fn backoff() -> ConstantBuilder {
+        ConstantBuilder::default()
+            .with_delay(Duration::from_millis(1))
+            .with_max_times(1)
+    }
+
+    #[test]
+    async fn test_limit_give_up() {
+        let limiter = RetryLimiter::new(1).with_give_up();
+        let attempts = spin::Mutex::new(0);
+        let f = || {
+            *attempts.lock() += 1;
+            ready(Err::<(), _>("error"))
+        };
+
+        // The first retry holds the only permit while it sleeps.
+        let mut first = pin!(f
+            .retry(backoff())
+            .sleep(|_| core::future::pending::<()>())
+            .limit(limiter.clone()));
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(1, limiter.retrying());
+
+        // The second one gives up after its first attempt.
+        let result = f.retry(backoff()).limit(limiter.clone()).await;
+        assert_eq!(Err("error"), result);
+        assert_eq!(2, *attempts.lock());
+        assert_eq!(1, limiter.retrying());
+    }
+
+    #[test]
+    async fn test_limit_wait() {
+        let limiter = RetryLimiter::new(1);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut first = Box::pin(
+            (|| ready(Err::<(), _>("error")))
+                .retry(backoff())
+                .sleep(|_| core::future::pending::<()>())
+                .limit(limiter.clone()),
+        );
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+
+        // The second retry waits for the permit before its next attempt.
+        let attempts = spin::Mutex::new(0);
+        let mut second = pin!((|ctx: ()| {
+            *attempts.lock() += 1;
+            ready((ctx, Err::<(), _>("error")))
+        })
+        .retry(backoff())
+        .sleep(|_| ready(()))
+        .limit(limiter.clone())
+        .context(()));
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(1, *attempts.lock());
+
+        drop(first);
+        let Poll::Ready((_, result)) = second.as_mut().poll(&mut cx) else {
+            panic!("retry must be ready");
+        };
+        assert_eq!(Err("error"), result);
+        assert_eq!(2, *attempts.lock());
+        assert_eq!(0, limiter.retrying());
+    }
+
+    #[test]
+    async fn test_limit_release_on_drop() {
+        let limiter = RetryLimiter::new(1);
+
+        {
+            let mut retry = pin!((|| ready(Err::<(), _>("error")))
+                .retry(backoff())
+                .sleep(|_| core::future::pending::<()>())
+                .limit(limiter.clone()));
+            let waker = noop_waker();
+            let mut cx = Context::from_waker(&waker);
+            assert!(retry.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(1, limiter.retrying());
+        }
+
+        assert_eq!(0, limiter.retrying());
+    }
+
+    fn noop_waker() -> Waker {
+        struct Noop;
+
+        impl std::task::Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        Waker::from(Arc::new(Noop))
+    }
+}
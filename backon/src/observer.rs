@@ -0,0 +1,375 @@
+use core::time::Duration;
+
+/// RetryObserver is notified about every step of a retry.
+///
+/// Unlike `notify`, which only fires before sleeping, an observer sees the whole
+/// lifecycle of a retry: every attempt, every error, the scheduled retries and
+/// the final outcome. Implement it once and attach it to [`Retry`](crate::Retry),
+/// [`RetryWithContext`](crate::RetryWithContext), [`BlockingRetry`](crate::BlockingRetry)
+/// or [`BlockingRetryWithContext`](crate::BlockingRetryWithContext) with `observe`.
+///
+/// `attempt` starts from `1` for the first attempt. All methods do nothing by
+/// default, so only the interesting ones need to be implemented.
+///
+/// Multiple observers can be combined into a tuple or a `Vec`, which notifies
+/// them in order. `()` is an observer that does nothing.
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use backon::BlockingRetryable;
+/// use backon::ConstantBuilder;
+/// use backon::RetryObserver;
+///
+/// #[derive(Default)]
+/// struct Attempts(usize);
+///
+/// impl<E> RetryObserver<E> for Attempts {
+///     fn on_attempt_start(&mut self, attempt: usize) {
+///         self.0 = attempt;
+///     }
+/// }
+///
+/// let mut attempts = Attempts::default();
+/// let result = (|| Err::<(), _>("error"))
+///     .retry(ConstantBuilder::default().with_delay(Duration::ZERO))
+///     .observe(&mut attempts)
+///     .call();
+///
+/// assert!(result.is_err());
+/// // The first attempt and 3 retries.
+/// assert_eq!(4, attempts.0);
+/// ```
+pub trait RetryObserver<E> {
+    /// Called before an attempt is started.
+    fn on_attempt_start(&mut self, attempt: usize) {
+        let _ = attempt;
+    }
+
+    /// Called when an attempt fails, before deciding whether to retry.
+    fn on_attempt_error(&mut self, attempt: usize, err: &E) {
+        let _ = (attempt, err);
+    }
+
+    /// Called when another attempt is scheduled after sleeping for `delay`.
+    fn on_retry_scheduled(&mut self, attempt: usize, err: &E, delay: Duration) {
+        let _ = (attempt, err, delay);
+    }
+
+    /// Called when an attempt succeeds.
+    fn on_success(&mut self, attempt: usize) {
+        let _ = attempt;
+    }
+
+    /// Called when the retry gives up and returns `err`.
+    fn on_give_up(&mut self, attempt: usize, err: &E) {
+        let _ = (attempt, err);
+    }
+}
+
+impl<E> RetryObserver<E> for () {}
+
+impl<E, O: RetryObserver<E> + ?Sized> RetryObserver<E> for &mut O {
+    fn on_attempt_start(&mut self, attempt: usize) {
+        (**self).on_attempt_start(attempt)
+    }
+
+    fn on_attempt_error(&mut self, attempt: usize, err: &E) {
+        (**self).on_attempt_error(attempt, err)
+    }
+
+    fn on_retry_scheduled(&mut self, attempt: usize, err: &E, delay: Duration) {
+        (**self).on_retry_scheduled(attempt, err, delay)
+    }
+
+    fn on_success(&mut self, attempt: usize) {
+        (**self).on_success(attempt)
+    }
+
+    fn on_give_up(&mut self, attempt: usize, err: &E) {
+        (**self).on_give_up(attempt, err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E, O: RetryObserver<E> + ?Sized> RetryObserver<E> for std::boxed::Box<O> {
+    fn on_attempt_start(&mut self, attempt: usize) {
+        (**self).on_attempt_start(attempt)
+    }
+
+    fn on_attempt_error(&mut self, attempt: usize, err: &E) {
+        (**self).on_attempt_error(attempt, err)
+    }
+
+    fn on_retry_scheduled(&mut self, attempt: usize, err: &E, delay: Duration) {
+        (**self).on_retry_scheduled(attempt, err, delay)
+    }
+
+    fn on_success(&mut self, attempt: usize) {
+        (**self).on_success(attempt)
+    }
+
+    fn on_give_up(&mut self, attempt: usize, err: &E) {
+        (**self).on_give_up(attempt, err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E, O: RetryObserver<E>> RetryObserver<E> for std::vec::Vec<O> {
+    fn on_attempt_start(&mut self, attempt: usize) {
+        self.iter_mut().for_each(|o| o.on_attempt_start(attempt))
+    }
+
+    fn on_attempt_error(&mut self, attempt: usize, err: &E) {
+        self.iter_mut()
+            .for_each(|o| o.on_attempt_error(attempt, err))
+    }
+
+    fn on_retry_scheduled(&mut self, attempt: usize, err: &E, delay: Duration) {
+        self.iter_mut()
+            .for_each(|o| o.on_retry_scheduled(attempt, err, delay))
+    }
+
+    fn on_success(&mut self, attempt: usize) {
+        self.iter_mut().for_each(|o| o.on_success(attempt))
+    }
+
+    fn on_give_up(&mut self, attempt: usize, err: &E) {
+        self.iter_mut().for_each(|o| o.on_give_up(attempt, err))
+    }
+}
+
+macro_rules! impl_tuple_observer {
+    ($($name:ident: $idx:tt),+) => {
+        impl<E, $($name: RetryObserver<E>),+> RetryObserver<E> for ($($name,)+) {
+            fn on_attempt_start(&mut self, attempt: usize) {
+                $(self.$idx.on_attempt_start(attempt);)+
+            }
+
+            fn on_attempt_error(&mut self, attempt: usize, err: &E) {
+                $(self.$idx.on_attempt_error(attempt, err);)+
+            }
+
+            fn on_retry_scheduled(&mut self, attempt: usize, err: &E, delay: Duration) {
+                $(self.$idx.on_retry_scheduled(attempt, err, delay);)+
+            }
+
+            fn on_success(&mut self, attempt: usize) {
+                $(self.$idx.on_success(attempt);)+
+            }
+
+            fn on_give_up(&mut self, attempt: usize, err: &E) {
+                $(self.$idx.on_give_up(attempt, err);)+
+            }
+        }
+    };
+}
+
+impl_tuple_observer!(A: 0);
+impl_tuple_observer!(A: 0, B: 1);
+impl_tuple_observer!(A: 0, B: 1, C: 2);
+impl_tuple_observer!(A: 0, B: 1, C: 2, D: 3);
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::future::ready;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::BlockingRetryable;
+    use crate::BlockingRetryableWithContext;
+    use crate::ConstantBuilder;
+    use crate::Retryable;
+    use crate::RetryableWithContext;
+
+    /// Records every event as a line prefixed with `name`.
+    struct Recorder<'a> {
+        name: &'static str,
+        lines: &'a spin::Mutex<Vec<String>>,
+    }
+
+    impl<'a> Recorder<'a> {
+        fn new(name: &'static str, lines: &'a spin::Mutex<Vec<String>>) -> Self {
+            Recorder { name, lines }
+        }
+
+        fn push(&self, line: String) {
+            self.lines.lock().push(format!("{} {}", self.name, line));
+        }
+    }
+
+    impl RetryObserver<&str> for Recorder<'_> {
+        fn on_attempt_start(&mut self, attempt: usize) {
+            self.push(format!("start {attempt}"));
+        }
+
+        fn on_attempt_error(&mut self, attempt: usize, err: &&str) {
+            self.push(format!("error {attempt} {err}"));
+        }
+
+        fn on_retry_scheduled(&mut self, attempt: usize, err: &&str, delay: Duration) {
+            self.push(format!("retry {attempt} {err} {delay:?}"));
+        }
+
+        fn on_success(&mut self, attempt: usize) {
+            self.push(format!("success {attempt}"));
+        }
+
+        fn on_give_up(&mut self, attempt: usize, err: &&str) {
+            self.push(format!("give up {attempt} {err}"));
+        }
+    }
+
+    fn backoff() -> ConstantBuilder {
+        ConstantBuilder::default()
+            .with_delay(Duration::from_millis(1))
+            .with_max_times(1)
+    }
+
+    const GIVE_UP: [&str; 6] = [
+        "a start 1",
+        "a error 1 error",
+        "a retry 1 error 1ms",
+        "a start 2",
+        "a error 2 error",
+        "a give up 2 error",
+    ];
+
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    #[cfg_attr(target_arch = "wasm32", test)]
+    async fn test_observe_retry() {
+        let lines = spin::Mutex::new(Vec::new());
+        let mut attempts = 0;
+
+        let result = (|| {
+            attempts += 1;
+            ready(if attempts < 2 { Err("error") } else { Ok(()) })
+        })
+        .retry(backoff())
+        .sleep(|_| ready(()))
+        .observe(Recorder::new("a", &lines))
+        .await;
+
+        assert_eq!(Ok(()), result);
+        assert_eq!(
+            vec![
+                "a start 1",
+                "a error 1 error",
+                "a retry 1 error 1ms",
+                "a start 2",
+                "a success 2",
+            ],
+            *lines.lock()
+        );
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    #[cfg_attr(target_arch = "wasm32", test)]
+    async fn test_observe_retry_with_context() {
+        let lines = spin::Mutex::new(Vec::new());
+
+        let (_, result) = (|ctx: ()| ready((ctx, Err::<(), _>("error"))))
+            .retry(backoff())
+            .sleep(|_| ready(()))
+            .observe(Recorder::new("a", &lines))
+            .context(())
+            .await;
+
+        assert_eq!(Err("error"), result);
+        assert_eq!(GIVE_UP.to_vec(), *lines.lock());
+    }
+
+    #[test]
+    fn test_observe_blocking_retry() {
+        let lines = spin::Mutex::new(Vec::new());
+
+        let result = (|| Err::<(), _>("error"))
+            .retry(backoff())
+            .sleep(|_| {})
+            .observe(Recorder::new("a", &lines))
+            .call();
+
+        assert_eq!(Err("error"), result);
+        assert_eq!(GIVE_UP.to_vec(), *lines.lock());
+    }
+
+    #[test]
+    fn test_observe_blocking_retry_with_context() {
+        let lines = spin::Mutex::new(Vec::new());
+
+        let (_, result) = (|ctx: ()| (ctx, Err::<(), _>("error")))
+            .retry(backoff())
+            .sleep(|_| {})
+            .observe(Recorder::new("a", &lines))
+            .context(())
+            .call();
+
+        assert_eq!(Err("error"), result);
+        assert_eq!(GIVE_UP.to_vec(), *lines.lock());
+    }
+
+    #[test]
+    fn test_observe_not_retryable() {
+        let lines = spin::Mutex::new(Vec::new());
+
+        let result = (|| Err::<(), _>("error"))
+            .retry(backoff())
+            .sleep(|_| {})
+            .when(|_| false)
+            .observe(Recorder::new("a", &lines))
+            .call();
+
+        assert_eq!(Err("error"), result);
+        assert_eq!(
+            vec!["a start 1", "a error 1 error", "a give up 1 error"],
+            *lines.lock()
+        );
+    }
+
+    #[test]
+    fn test_observe_fan_out() {
+        let lines = spin::Mutex::new(Vec::new());
+
+        let _ = (|| Ok::<_, &str>(()))
+            .retry(backoff())
+            .sleep(|_| {})
+            .observe((Recorder::new("a", &lines), (), Recorder::new("b", &lines)))
+            .call();
+
+        assert_eq!(
+            vec!["a start 1", "b start 1", "a success 1", "b success 1"],
+            *lines.lock()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_observe_vec() {
+        let lines = spin::Mutex::new(Vec::new());
+        let observers: Vec<std::boxed::Box<dyn RetryObserver<&str> + '_>> = vec![
+            std::boxed::Box::new(Recorder::new("a", &lines)),
+            std::boxed::Box::new(Recorder::new("b", &lines)),
+        ];
+
+        let _ = (|| Ok::<_, &str>(()))
+            .retry(backoff())
+            .sleep(|_| {})
+            .observe(observers)
+            .call();
+
+        assert_eq!(
+            vec!["a start 1", "b start 1", "a success 1", "b success 1"],
+            *lines.lock()
+        );
+    }
+}
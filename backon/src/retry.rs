@@ -1,4 +1,10 @@
+#[cfg(feature = "tracing")]
+use core::fmt::Debug;
+#[cfg(feature = "tracing")]
+use core::fmt::Display;
+use core::future::pending;
 use core::future::Future;
+use core::future::Pending;
 use core::pin::Pin;
 use core::task::ready;
 use core::task::Context;
@@ -6,9 +12,19 @@ use core::task::Poll;
 use core::time::Duration;
 
 use crate::backoff::BackoffBuilder;
+use crate::backoff::ClassifiedBackoff;
+use crate::backoff::ErrorBackoff;
+use crate::failover::FailoverPrimary;
+use crate::instrument::GiveUp;
+use crate::instrument::Instrument;
+use crate::limiter::Limit;
 use crate::sleep::MaybeSleeper;
-use crate::Backoff;
 use crate::DefaultSleeper;
+use crate::Failover;
+#[cfg(feature = "std")]
+use crate::RetryLimiter;
+use crate::RetryObserver;
+use crate::RetryableError;
 use crate::Sleeper;
 
 /// Retryable will add retry support for functions that produce futures with results.
@@ -63,7 +79,7 @@ where
 
 /// Struct generated by [`Retryable`].
 pub struct Retry<
-    B: Backoff,
+    B: ErrorBackoff<E>,
     T,
     E,
     Fut: Future<Output = Result<T, E>>,
@@ -72,6 +88,8 @@ pub struct Retry<
     RF = fn(&E) -> bool,
     NF = fn(&E, Duration),
     AF = fn(&E, Option<Duration>) -> Option<Duration>,
+    CF = Pending<()>,
+    OB = (),
 > {
     backoff: B,
     future_fn: FutureFn,
@@ -81,12 +99,20 @@ pub struct Retry<
     sleep_fn: SF,
     adjust_fn: AF,
 
+    cancel: CF,
+    /// Set once `cancel` has fired, no more attempts will be started.
+    cancelled: bool,
+
+    instrument: Instrument<E>,
+    limit: Limit,
+    observer: OB,
+
     state: State<T, E, Fut, SF::Sleep>,
 }
 
 impl<B, T, E, Fut, FutureFn> Retry<B, T, E, Fut, FutureFn>
 where
-    B: Backoff,
+    B: ErrorBackoff<E>,
     Fut: Future<Output = Result<T, E>>,
     FutureFn: FnMut() -> Fut,
 {
@@ -101,14 +127,21 @@ where
             adjust_fn: |_: &E, dur: Option<Duration>| dur,
             sleep_fn: DefaultSleeper::default(),
 
+            cancel: pending(),
+            cancelled: false,
+            instrument: Instrument::new(),
+            limit: Limit::new(),
+            observer: (),
+
             state: State::Idle,
         }
     }
 }
 
-impl<B, T, E, Fut, FutureFn, SF, RF, NF, AF> Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF>
+impl<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
+    Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
 where
-    B: Backoff,
+    B: ErrorBackoff<E>,
     Fut: Future<Output = Result<T, E>>,
     FutureFn: FnMut() -> Fut,
     SF: MaybeSleeper,
@@ -147,7 +180,10 @@ where
     ///     Ok(())
     /// }
     /// ```
-    pub fn sleep<SN: Sleeper>(self, sleep_fn: SN) -> Retry<B, T, E, Fut, FutureFn, SN, RF, NF, AF> {
+    pub fn sleep<SN: Sleeper>(
+        self,
+        sleep_fn: SN,
+    ) -> Retry<B, T, E, Fut, FutureFn, SN, RF, NF, AF, CF, OB> {
         Retry {
             backoff: self.backoff,
             retryable_fn: self.retryable_fn,
@@ -155,6 +191,11 @@ where
             future_fn: self.future_fn,
             sleep_fn,
             adjust_fn: self.adjust_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: State::Idle,
         }
     }
@@ -191,7 +232,7 @@ where
     pub fn when<RN: FnMut(&E) -> bool>(
         self,
         retryable: RN,
-    ) -> Retry<B, T, E, Fut, FutureFn, SF, RN, NF, AF> {
+    ) -> Retry<B, T, E, Fut, FutureFn, SF, RN, NF, AF, CF, OB> {
         Retry {
             backoff: self.backoff,
             retryable_fn: retryable,
@@ -199,6 +240,11 @@ where
             future_fn: self.future_fn,
             sleep_fn: self.sleep_fn,
             adjust_fn: self.adjust_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: self.state,
         }
     }
@@ -241,7 +287,7 @@ where
     pub fn notify<NN: FnMut(&E, Duration)>(
         self,
         notify: NN,
-    ) -> Retry<B, T, E, Fut, FutureFn, SF, RF, NN, AF> {
+    ) -> Retry<B, T, E, Fut, FutureFn, SF, RF, NN, AF, CF, OB> {
         Retry {
             backoff: self.backoff,
             retryable_fn: self.retryable_fn,
@@ -249,6 +295,71 @@ where
             sleep_fn: self.sleep_fn,
             future_fn: self.future_fn,
             adjust_fn: self.adjust_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
+            state: self.state,
+        }
+    }
+
+    /// Attach a [`RetryObserver`] to be notified about every step of this retry.
+    ///
+    /// Unlike `notify`, the observer also sees the first attempt, successes and
+    /// the final error. Use a tuple or a `Vec` to attach multiple observers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use backon::ExponentialBuilder;
+    /// use backon::Retryable;
+    /// use backon::RetryObserver;
+    ///
+    /// struct Logger;
+    ///
+    /// impl RetryObserver<anyhow::Error> for Logger {
+    ///     fn on_attempt_start(&mut self, attempt: usize) {
+    ///         println!("starting attempt {attempt}");
+    ///     }
+    ///
+    ///     fn on_give_up(&mut self, attempt: usize, err: &anyhow::Error) {
+    ///         println!("giving up after {attempt} attempts: {err}");
+    ///     }
+    /// }
+    ///
+    /// async fn fetch() -> Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<()> {
+    ///     let content = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         .observe(Logger)
+    ///         .await?;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn observe<ON: RetryObserver<E>>(
+        self,
+        observer: ON,
+    ) -> Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, ON> {
+        Retry {
+            backoff: self.backoff,
+            retryable_fn: self.retryable_fn,
+            notify_fn: self.notify_fn,
+            sleep_fn: self.sleep_fn,
+            future_fn: self.future_fn,
+            adjust_fn: self.adjust_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer,
             state: self.state,
         }
     }
@@ -262,16 +373,20 @@ where
     /// If no `adjust` function is specified, the original backoff duration from the input will be used without modification.
     ///
     /// `adjust` can be used to implement dynamic backoff strategies, such as adjust backoff values from the http `Retry-After` headers.
+    /// With the `http` feature enabled, [`http::retry_after`](crate::http::retry_after) and
+    /// [`http::retry_after_from_response`](crate::http::retry_after_from_response) parse them for you.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use core::time::Duration;
+    /// # #[cfg(feature = "http")]
+    /// # mod example {
     /// use std::error::Error;
     /// use std::fmt::Display;
     /// use std::fmt::Formatter;
     ///
     /// use anyhow::Result;
+    /// use backon::http::retry_after;
     /// use backon::ExponentialBuilder;
     /// use backon::Retryable;
     /// use reqwest::header::HeaderMap;
@@ -305,31 +420,23 @@ where
     /// async fn main() -> Result<()> {
     ///     let content = fetch
     ///         .retry(ExponentialBuilder::default())
-    ///         .adjust(|err, dur| {
-    ///             match err.downcast_ref::<HttpError>() {
-    ///                 Some(v) => {
-    ///                     if let Some(retry_after) = v.headers.get("Retry-After") {
-    ///                         // Parse the Retry-After header and adjust the backoff duration
-    ///                         let retry_after = retry_after.to_str().unwrap_or("0");
-    ///                         let retry_after = retry_after.parse::<u64>().unwrap_or(0);
-    ///                         Some(Duration::from_secs(retry_after))
-    ///                     } else {
-    ///                         dur
-    ///                     }
-    ///                 }
-    ///                 None => dur,
-    ///             }
+    ///         .adjust(|err, dur| match err.downcast_ref::<HttpError>() {
+    ///             // Honor the server's delay, but stop once the backoff is exhausted.
+    ///             Some(v) => dur.map(|dur| retry_after(&v.headers).unwrap_or(dur)),
+    ///             None => dur,
     ///         })
     ///         .await?;
     ///     println!("fetch succeeded: {}", content);
     ///
     ///     Ok(())
     /// }
+    /// # }
+    /// # fn main() {}
     /// ```
     pub fn adjust<NAF: FnMut(&E, Option<Duration>) -> Option<Duration>>(
         self,
         adjust: NAF,
-    ) -> Retry<B, T, E, Fut, FutureFn, SF, RF, NF, NAF> {
+    ) -> Retry<B, T, E, Fut, FutureFn, SF, RF, NF, NAF, CF, OB> {
         Retry {
             backoff: self.backoff,
             retryable_fn: self.retryable_fn,
@@ -337,9 +444,366 @@ where
             sleep_fn: self.sleep_fn,
             future_fn: self.future_fn,
             adjust_fn: adjust,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: self.state,
         }
     }
+
+    /// Use a different backoff for errors matching `class`.
+    ///
+    /// Errors for which `class` returns `true` sleep with the backoff built from
+    /// `builder`, all other errors keep using the current backoff. Every backoff
+    /// has its own attempt counter and limit, retrying gives up as soon as the
+    /// backoff for the current error is exhausted.
+    ///
+    /// `backoff_for` can be called multiple times, the class added last is checked first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use core::time::Duration;
+    ///
+    /// use backon::ConstantBuilder;
+    /// use backon::ExponentialBuilder;
+    /// use backon::Retryable;
+    ///
+    /// #[derive(Debug)]
+    /// enum Error {
+    ///     TooManyRequests,
+    ///     ConnectionReset,
+    /// }
+    ///
+    /// async fn fetch() -> Result<String, Error> {
+    ///     Err(Error::ConnectionReset)
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let result = fetch
+    ///         // Connection resets are retried quickly.
+    ///         .retry(ExponentialBuilder::default().with_min_delay(Duration::from_millis(10)))
+    ///         // Rate limited requests wait for the quota to recover.
+    ///         .backoff_for(
+    ///             |e: &Error| matches!(e, Error::TooManyRequests),
+    ///             ConstantBuilder::default()
+    ///                 .with_delay(Duration::from_secs(10))
+    ///                 .with_max_times(6),
+    ///         )
+    ///         .await;
+    ///     println!("fetch result: {:?}", result);
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn backoff_for<P, BB>(
+        self,
+        class: P,
+        builder: BB,
+    ) -> Retry<ClassifiedBackoff<B, P, BB::Backoff>, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
+    where
+        P: FnMut(&E) -> bool + Send + Sync + Unpin,
+        BB: BackoffBuilder,
+    {
+        Retry {
+            backoff: ClassifiedBackoff::new(self.backoff, class, builder.build()),
+            retryable_fn: self.retryable_fn,
+            notify_fn: self.notify_fn,
+            sleep_fn: self.sleep_fn,
+            future_fn: self.future_fn,
+            adjust_fn: self.adjust_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
+            state: self.state,
+        }
+    }
+
+    /// Stop retrying once `signal` completes.
+    ///
+    /// Cancellation is graceful: an attempt that is already running is allowed to
+    /// finish and its result is returned as is, but no more attempts are started.
+    /// If the retry is sleeping, it stops immediately and returns the error that
+    /// scheduled the retry, which has already been passed to `notify`.
+    ///
+    /// The operation is always attempted at least once. Any future can be used as
+    /// the signal, like `tokio::sync::Notify::notified()` or
+    /// `tokio_util::sync::CancellationToken::cancelled_owned()`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use backon::ExponentialBuilder;
+    /// use backon::Retryable;
+    /// use tokio::sync::oneshot;
+    ///
+    /// async fn fetch() -> Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<()> {
+    ///     let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    ///
+    ///     let handle = tokio::spawn(
+    ///         fetch
+    ///             .retry(ExponentialBuilder::default())
+    ///             .cancel_on(async move {
+    ///                 let _ = shutdown_rx.await;
+    ///             }),
+    ///     );
+    ///
+    ///     // Shutting down, the retry returns the last error instead of sleeping.
+    ///     let _ = shutdown_tx.send(());
+    ///     let content = handle.await??;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn cancel_on<CN: Future<Output = ()>>(
+        self,
+        signal: CN,
+    ) -> Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CN, OB> {
+        Retry {
+            backoff: self.backoff,
+            retryable_fn: self.retryable_fn,
+            notify_fn: self.notify_fn,
+            sleep_fn: self.sleep_fn,
+            future_fn: self.future_fn,
+            adjust_fn: self.adjust_fn,
+            cancel: signal,
+            cancelled: false,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
+            state: self.state,
+        }
+    }
+
+    /// Emit [`tracing`] spans and events for this retry.
+    ///
+    /// A span named `retry` covers the whole retry loop and a span named `attempt`
+    /// covers every attempt. `name` is not the name of these spans: tracing needs
+    /// span names at compile time, so `name` is recorded as a `name` field on both
+    /// spans to tell retries apart. Events are emitted when an attempt succeeds,
+    /// when an attempt fails and another one is scheduled after `delay`, and when
+    /// the retry gives up with a `reason`. Errors are recorded with both their
+    /// `Display` and `Debug` output.
+    ///
+    /// `name` is a `&'static str` so that tracing doesn't need an allocator. To
+    /// record values only known at runtime, such as an endpoint, enter a span of
+    /// your own around the retry instead: the `retry` span is created as its child.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use backon::ExponentialBuilder;
+    /// use backon::Retryable;
+    ///
+    /// async fn fetch() -> Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<()> {
+    ///     let content = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         .trace("fetch")
+    ///         .await?;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "tracing")]
+    pub fn trace(mut self, name: &'static str) -> Self
+    where
+        E: Display + Debug,
+    {
+        self.instrument = self.instrument.with_tracing(name);
+        self
+    }
+
+    /// Record [`metrics`] for this retry, labeled by `policy`.
+    ///
+    /// `policy` is usually the name of the dependency being retried, it can be a
+    /// `&'static str` or a `String` built at runtime.
+    ///
+    /// The following metrics are recorded, all of them carry a `policy` label:
+    ///
+    /// - `backon_attempts_total`: counter of started attempts, including the first one.
+    /// - `backon_retries_total`: counter of retries, counted when the attempt after
+    ///   the sleep starts. Retries cancelled or interrupted while sleeping are not counted.
+    /// - `backon_give_ups_total`: counter of retries that returned an error, with
+    ///   a `reason` label like `not_retryable` or `exhausted`.
+    /// - `backon_successes_after_retry_total`: counter of successes that needed
+    ///   more than one attempt.
+    /// - `backon_sleep_seconds`: histogram of the durations slept before retrying,
+    ///   recorded together with `backon_retries_total`.
+    ///
+    /// Every call starts one attempt plus one attempt per counted retry, so the
+    /// retry amplification of every dependency is
+    /// `backon_attempts_total / (backon_attempts_total - backon_retries_total)`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use backon::ExponentialBuilder;
+    /// use backon::Retryable;
+    ///
+    /// async fn fetch() -> Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<()> {
+    ///     let content = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         .metrics("fetch")
+    ///         .await?;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, policy: impl Into<metrics::SharedString>) -> Self {
+        self.instrument = self.instrument.with_metrics(policy.into());
+        self
+    }
+
+    /// Limit how many retries can be retrying at once with a shared [`RetryLimiter`].
+    ///
+    /// The first attempt is never limited. Once the limiter is full, this retry
+    /// waits before its next attempt, or gives up with its last error if the
+    /// limiter is configured to [give up](RetryLimiter::with_give_up).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use backon::ExponentialBuilder;
+    /// use backon::RetryLimiter;
+    /// use backon::Retryable;
+    ///
+    /// async fn fetch() -> Result<String> {
+    ///     Ok("hello, world!".to_string())
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<()> {
+    ///     let limiter = RetryLimiter::new(8);
+    ///
+    ///     let content = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         .limit(limiter.clone())
+    ///         .await?;
+    ///     println!("fetch succeeded: {}", content);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn limit(mut self, limiter: RetryLimiter) -> Self {
+        self.limit = self.limit.with_limiter(limiter);
+        self
+    }
+
+    /// Fail over to another operation once this retry has given up.
+    ///
+    /// `next` is usually another `Retry` with its own backoff, see [`Failover`]
+    /// for details.
+    pub fn failover<S>(self, next: S) -> Failover<FailoverPrimary<Self>, S> {
+        Failover::new(self, next)
+    }
+}
+
+impl<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
+    Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
+where
+    B: ErrorBackoff<E>,
+    E: RetryableError,
+    Fut: Future<Output = Result<T, E>>,
+    FutureFn: FnMut() -> Fut,
+    SF: MaybeSleeper,
+    RF: FnMut(&E) -> bool,
+    NF: FnMut(&E, Duration),
+    AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
+{
+    /// Classify errors with their [`RetryableError`] implementation.
+    ///
+    /// This is a shortcut for `.when(E::is_retryable)` together with an `adjust`
+    /// that sleeps for [`RetryableError::retry_after`] when the error provides one.
+    /// The backoff still decides whether another attempt is allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use core::time::Duration;
+    ///
+    /// use backon::ExponentialBuilder;
+    /// use backon::Retryable;
+    /// use backon::RetryableError;
+    ///
+    /// #[derive(Debug)]
+    /// enum Error {
+    ///     RateLimited(Duration),
+    ///     NotFound,
+    /// }
+    ///
+    /// impl RetryableError for Error {
+    ///     fn is_retryable(&self) -> bool {
+    ///         matches!(self, Error::RateLimited(_))
+    ///     }
+    ///
+    ///     fn retry_after(&self) -> Option<Duration> {
+    ///         match self {
+    ///             Error::RateLimited(dur) => Some(*dur),
+    ///             Error::NotFound => None,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// async fn fetch() -> Result<String, Error> {
+    ///     Err(Error::NotFound)
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let result = fetch
+    ///         .retry(ExponentialBuilder::default())
+    ///         .when_retryable()
+    ///         .await;
+    ///     assert!(result.is_err());
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn when_retryable(
+        self,
+    ) -> Retry<
+        B,
+        T,
+        E,
+        Fut,
+        FutureFn,
+        SF,
+        fn(&E) -> bool,
+        NF,
+        fn(&E, Option<Duration>) -> Option<Duration>,
+        CF,
+        OB,
+    > {
+        self.when(E::is_retryable as fn(&E) -> bool)
+            .adjust(crate::retryable_error::adjust::<E>)
+    }
 }
 
 /// State maintains internal state of retry.
@@ -348,19 +812,24 @@ enum State<T, E, Fut: Future<Output = Result<T, E>>, SleepFut: Future<Output = (
     #[default]
     Idle,
     Polling(Fut),
-    Sleeping(SleepFut),
+    /// The error is kept to be returned if the retry is cancelled while sleeping.
+    Sleeping((Option<E>, SleepFut)),
+    /// Waiting for the limiter before the next attempt, the error is kept like `Sleeping`.
+    Waiting(Option<E>),
 }
 
-impl<B, T, E, Fut, FutureFn, SF, RF, NF, AF> Future
-    for Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF>
+impl<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB> Future
+    for Retry<B, T, E, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
 where
-    B: Backoff,
+    B: ErrorBackoff<E>,
     Fut: Future<Output = Result<T, E>>,
     FutureFn: FnMut() -> Fut,
     SF: Sleeper,
     RF: FnMut(&E) -> bool,
     NF: FnMut(&E, Duration),
     AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
+    CF: Future<Output = ()>,
+    OB: RetryObserver<E>,
 {
     type Output = Result<T, E>;
 
@@ -371,9 +840,31 @@ where
         // We do the exactly same thing like `pin_project` but without depending on it directly.
         let this = unsafe { self.get_unchecked_mut() };
 
+        if !this.cancelled {
+            // Safety: This is safe because we don't move the `Retry` struct and this fut.
+            //
+            // We do the exactly same thing like `pin_project` but without depending on it directly.
+            let cancel = unsafe { Pin::new_unchecked(&mut this.cancel) };
+            this.cancelled = cancel.poll(cx).is_ready();
+        }
+        let _span = this.instrument.enter();
+        if this.cancelled {
+            // Stop sleeping and return the error that scheduled this retry.
+            if let State::Sleeping((err, _)) | State::Waiting(err) = &mut this.state {
+                let err = err.take().expect("error must be valid");
+                this.state = State::Idle;
+                this.limit.release();
+                this.instrument.give_up(&err, GiveUp::Cancelled);
+                this.observer.on_give_up(this.instrument.attempt(), &err);
+                return Poll::Ready(Err(err));
+            }
+        }
+
         loop {
             match &mut this.state {
                 State::Idle => {
+                    this.instrument.attempt_start();
+                    this.observer.on_attempt_start(this.instrument.attempt());
                     let fut = (this.future_fn)();
                     this.state = State::Polling(fut);
                     continue;
@@ -385,26 +876,61 @@ where
                     // We do the exactly same thing like `pin_project` but without depending on it directly.
                     let mut fut = unsafe { Pin::new_unchecked(fut) };
 
-                    match ready!(fut.as_mut().poll(cx)) {
-                        Ok(v) => return Poll::Ready(Ok(v)),
+                    let result = {
+                        let _attempt = this.instrument.enter_attempt();
+                        ready!(fut.as_mut().poll(cx))
+                    };
+                    let attempt = this.instrument.attempt();
+                    match result {
+                        Ok(v) => {
+                            this.instrument.success();
+                            this.observer.on_success(attempt);
+                            this.limit.release();
+                            return Poll::Ready(Ok(v));
+                        }
                         Err(err) => {
-                            // If input error is not retryable, return error directly.
+                            this.observer.on_attempt_error(attempt, &err);
+                            // If input error is not retryable or retry has been cancelled,
+                            // return error directly.
+                            if this.cancelled {
+                                this.instrument.give_up(&err, GiveUp::Cancelled);
+                                this.observer.on_give_up(attempt, &err);
+                                this.limit.release();
+                                return Poll::Ready(Err(err));
+                            }
                             if !(this.retryable_fn)(&err) {
+                                this.instrument.give_up(&err, GiveUp::NotRetryable);
+                                this.observer.on_give_up(attempt, &err);
+                                this.limit.release();
                                 return Poll::Ready(Err(err));
                             }
-                            let adjusted_backoff = (this.adjust_fn)(&err, this.backoff.next());
+                            let adjusted_backoff =
+                                (this.adjust_fn)(&err, this.backoff.next_for(&err));
                             match adjusted_backoff {
-                                None => return Poll::Ready(Err(err)),
+                                None => {
+                                    this.instrument.give_up(&err, GiveUp::Exhausted);
+                                    this.observer.on_give_up(attempt, &err);
+                                    this.limit.release();
+                                    return Poll::Ready(Err(err));
+                                }
+                                Some(_) if !this.limit.admit() => {
+                                    this.instrument.give_up(&err, GiveUp::Limited);
+                                    this.observer.on_give_up(attempt, &err);
+                                    return Poll::Ready(Err(err));
+                                }
                                 Some(dur) => {
+                                    this.instrument.retry(&err, dur);
+                                    this.observer.on_retry_scheduled(attempt, &err, dur);
                                     (this.notify_fn)(&err, dur);
-                                    this.state = State::Sleeping(this.sleep_fn.sleep(dur));
+                                    let sl = this.sleep_fn.sleep(dur);
+                                    this.state = State::Sleeping((Some(err), sl));
                                     continue;
                                 }
                             }
                         }
                     }
                 }
-                State::Sleeping(sl) => {
+                State::Sleeping((err, sl)) => {
                     // Safety: This is safe because we don't move the `Retry` struct and this fut,
                     // only its internal state.
                     //
@@ -412,6 +938,11 @@ where
                     let mut sl = unsafe { Pin::new_unchecked(sl) };
 
                     ready!(sl.as_mut().poll(cx));
+                    this.state = State::Waiting(err.take());
+                    continue;
+                }
+                State::Waiting(_) => {
+                    ready!(this.limit.poll_wait(cx));
                     this.state = State::Idle;
                     continue;
                 }
@@ -421,7 +952,11 @@ where
 }
 
 #[cfg(test)]
-#[cfg(any(feature = "tokio-sleep", feature = "gloo-timers-sleep",))]
+#[cfg(any(
+    feature = "tokio-sleep",
+    feature = "gloo-timers-sleep",
+    feature = "async-io-sleep",
+))]
 mod default_sleeper_tests {
     extern crate alloc;
 
@@ -584,4 +1119,160 @@ mod custom_sleeper_tests {
         assert!(result.is_err());
         assert_eq!("test_query meets error", result.unwrap_err().to_string());
     }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+    #[test]
+    async fn test_retry_with_smol_sleeper() {
+        let result = always_error
+            .retry(
+                ExponentialBuilder::default()
+                    .with_min_delay(Duration::from_millis(1))
+                    .with_max_times(3),
+            )
+            .sleep(crate::SmolSleeper)
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!("test_query meets error", result.unwrap_err().to_string());
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum TestError {
+        RateLimited,
+        NotFound,
+    }
+
+    impl RetryableError for TestError {
+        fn is_retryable(&self) -> bool {
+            *self == TestError::RateLimited
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            Some(Duration::from_secs(30))
+        }
+    }
+
+    #[test]
+    async fn test_retry_when_retryable() {
+        let mut attempts = 0;
+        let mut sleeps = alloc::vec::Vec::new();
+
+        let result = (|| {
+            attempts += 1;
+            let err = if attempts < 3 {
+                TestError::RateLimited
+            } else {
+                TestError::NotFound
+            };
+            ready(Err::<(), _>(err))
+        })
+        .retry(ExponentialBuilder::default())
+        .sleep(|_| ready(()))
+        .when_retryable()
+        .notify(|_, dur| sleeps.push(dur))
+        .await;
+
+        assert_eq!(Err(TestError::NotFound), result);
+        assert_eq!(3, attempts);
+        // Delays requested by errors win over the backoff.
+        assert_eq!(alloc::vec![Duration::from_secs(30); 2], sleeps);
+    }
+
+    #[test]
+    async fn test_retry_when_retryable_keeps_max_times() {
+        let mut attempts = 0;
+
+        let result = (|| {
+            attempts += 1;
+            ready(Err::<(), _>(TestError::RateLimited))
+        })
+        .retry(ExponentialBuilder::default().with_max_times(2))
+        .sleep(|_| ready(()))
+        .when_retryable()
+        .await;
+
+        assert_eq!(Err(TestError::RateLimited), result);
+        assert_eq!(3, attempts);
+    }
+
+    #[test]
+    async fn test_retry_cancelled_before_start() {
+        let mut attempts = 0;
+
+        let result = (|| {
+            attempts += 1;
+            ready(Err::<(), _>(TestError::RateLimited))
+        })
+        .retry(ExponentialBuilder::default())
+        .sleep(|_| ready(()))
+        .cancel_on(ready(()))
+        .await;
+
+        // The operation is attempted once, but never retried.
+        assert_eq!(Err(TestError::RateLimited), result);
+        assert_eq!(1, attempts);
+    }
+
+    #[test]
+    async fn test_retry_cancel_while_sleeping() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let mut notified = 0;
+
+        let retry = (|| ready(Err::<(), _>(TestError::RateLimited)))
+            .retry(ExponentialBuilder::default())
+            // Sleep forever, only cancellation can stop the retry.
+            .sleep(|_| pending::<()>())
+            .notify(|_, _| notified += 1)
+            .cancel_on(async {
+                let _ = rx.await;
+            });
+
+        let (result, _) = tokio::join!(retry, async { tx.send(()).unwrap() });
+
+        assert_eq!(Err(TestError::RateLimited), result);
+        assert_eq!(1, notified);
+    }
+
+    #[test]
+    async fn test_retry_with_backoff_for() {
+        let mut attempts = 0;
+        let mut sleeps = alloc::vec::Vec::new();
+
+        let result = (|| {
+            attempts += 1;
+            // Alternate between both classes to make sure they count separately.
+            let err = if attempts % 2 == 0 {
+                TestError::RateLimited
+            } else {
+                TestError::NotFound
+            };
+            ready(Err::<(), _>(err))
+        })
+        .retry(
+            ExponentialBuilder::default()
+                .with_min_delay(Duration::from_millis(1))
+                .with_max_times(2),
+        )
+        .backoff_for(
+            |e: &TestError| *e == TestError::RateLimited,
+            crate::ConstantBuilder::default()
+                .with_delay(Duration::from_secs(10))
+                .with_max_times(5),
+        )
+        .sleep(|_| ready(()))
+        .notify(|_, dur| sleeps.push(dur))
+        .await;
+
+        assert_eq!(Err(TestError::NotFound), result);
+        assert_eq!(5, attempts);
+        assert_eq!(
+            alloc::vec![
+                Duration::from_millis(1),
+                Duration::from_secs(10),
+                Duration::from_millis(2),
+                Duration::from_secs(10),
+            ],
+            sleeps
+        );
+    }
 }
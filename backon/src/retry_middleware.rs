@@ -0,0 +1,339 @@
+use std::boxed::Box;
+
+use http::Extensions;
+use reqwest::Request;
+use reqwest::Response;
+use reqwest::StatusCode;
+use reqwest_middleware::Error;
+use reqwest_middleware::Middleware;
+use reqwest_middleware::Next;
+
+use crate::backoff::BackoffBuilder;
+use crate::http::retry_after;
+use crate::sleep::MaybeSleeper;
+use crate::DefaultSleeper;
+use crate::RetryableWithContext;
+use crate::Sleeper;
+
+/// RetryMiddleware is a [`reqwest_middleware::Middleware`] that retries transient failures.
+///
+/// The following failures are considered transient:
+///
+/// - Responses with status `408 Request Timeout`, `429 Too Many Requests` or any `5xx`.
+/// - Connection errors and timeouts returned by [`reqwest`].
+///
+/// Delays requested by the server via `Retry-After` (and the rate limit headers
+/// supported by [`crate::http::retry_after`]) are honored as long as the backoff
+/// still allows another attempt.
+///
+/// Once the backoff is exhausted, the last response or error is returned as is.
+/// Requests whose body can't be cloned (like streams) are sent only once.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use backon::ExponentialBuilder;
+/// use backon::RetryMiddleware;
+/// use reqwest_middleware::ClientBuilder;
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<()> {
+///     let client = ClientBuilder::new(reqwest::Client::new())
+///         .with(RetryMiddleware::new(ExponentialBuilder::default()))
+///         .build();
+///
+///     let content = client
+///         .get("https://www.rust-lang.org")
+///         .send()
+///         .await?
+///         .text()
+///         .await?;
+///     println!("fetch succeeded: {}", content);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryMiddleware<B: BackoffBuilder, SF: MaybeSleeper = DefaultSleeper> {
+    builder: B,
+    sleep_fn: SF,
+}
+
+impl<B> RetryMiddleware<B>
+where
+    B: BackoffBuilder,
+{
+    /// Create a new retry middleware with the given backoff builder.
+    pub fn new(builder: B) -> Self {
+        RetryMiddleware {
+            builder,
+            sleep_fn: DefaultSleeper::default(),
+        }
+    }
+}
+
+impl<B, SF> RetryMiddleware<B, SF>
+where
+    B: BackoffBuilder,
+    SF: MaybeSleeper,
+{
+    /// Set the sleeper for retrying.
+    ///
+    /// The sleeper should implement the [`Sleeper`] trait and be cloneable since
+    /// every request uses its own copy.
+    ///
+    /// If not specified, we use the [`DefaultSleeper`].
+    pub fn sleep<SN: Sleeper>(self, sleep_fn: SN) -> RetryMiddleware<B, SN> {
+        RetryMiddleware {
+            builder: self.builder,
+            sleep_fn,
+        }
+    }
+}
+
+impl<B, SF> RetryMiddleware<B, SF>
+where
+    B: BackoffBuilder + Clone,
+    SF: Sleeper + Clone,
+{
+    async fn execute(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        // There is no way to retry a request that can't be cloned.
+        let Some(first) = req.try_clone() else {
+            return next.run(req, extensions).await;
+        };
+
+        let (req, next) = (&req, &next);
+        let (_, result) = (|(ext, pending): (_, Option<Request>)| async move {
+            // Spell out the type so `ext` is reborrowed, not moved, by `run`.
+            let ext: &mut Extensions = ext;
+            // The first attempt sends the copy made above, later ones clone again.
+            let attempt = pending.or_else(|| req.try_clone());
+            let res = next
+                .clone()
+                .run(attempt.expect("request must be cloneable"), ext)
+                .await;
+
+            let res = match res {
+                Ok(resp) if is_transient_status(resp.status()) => Err(Attempt::Status(resp)),
+                Ok(resp) => Ok(resp),
+                Err(err) => Err(Attempt::Error(err)),
+            };
+            ((ext, None), res)
+        })
+        .retry(self.builder.clone())
+        .sleep(self.sleep_fn.clone())
+        .when(|e| match e {
+            Attempt::Status(_) => true,
+            Attempt::Error(err) => is_transient_error(err),
+        })
+        .adjust(|e, dur| match e {
+            // Honor the server's delay, but stop once the backoff is exhausted.
+            Attempt::Status(resp) => dur.map(|dur| retry_after(resp.headers()).unwrap_or(dur)),
+            Attempt::Error(_) => dur,
+        })
+        .context((extensions, Some(first)))
+        .await;
+
+        match result {
+            Ok(resp) | Err(Attempt::Status(resp)) => Ok(resp),
+            Err(Attempt::Error(err)) => Err(err),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait::async_trait]
+impl<B, SF> Middleware for RetryMiddleware<B, SF>
+where
+    B: BackoffBuilder + Clone + 'static,
+    SF: Sleeper + Clone + Send + Sync,
+    SF::Sleep: Send,
+{
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.execute(req, extensions, next).await
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[async_trait::async_trait(?Send)]
+impl<B, SF> Middleware for RetryMiddleware<B, SF>
+where
+    B: BackoffBuilder + Clone + 'static,
+    SF: Sleeper + Clone + Send + Sync,
+{
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.execute(req, extensions, next).await
+    }
+}
+
+/// The outcome of a failed attempt.
+enum Attempt {
+    /// The server responded with a transient status.
+    Status(Response),
+    /// The request failed without a response.
+    Error(Error),
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+fn is_transient_error(err: &Error) -> bool {
+    match err {
+        Error::Reqwest(err) => err.is_connect() || err.is_timeout(),
+        Error::Middleware(_) => false,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use core::future::ready;
+    use core::future::Ready;
+    use core::time::Duration;
+    use std::format;
+    use std::io::Read;
+    use std::io::Write;
+    use std::net::SocketAddr;
+    use std::net::TcpListener;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec;
+    use std::vec::Vec;
+
+    use reqwest::StatusCode;
+    use reqwest_middleware::ClientBuilder;
+
+    use super::*;
+    use crate::ConstantBuilder;
+
+    fn no_sleep(_: Duration) -> Ready<()> {
+        ready(())
+    }
+
+    fn http_client() -> reqwest::Client {
+        reqwest::Client::builder().no_proxy().build().unwrap()
+    }
+
+    /// Start a server that responds with the given statuses in order, repeating the last one.
+    fn serve(statuses: Vec<u16>) -> (SocketAddr, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let counter = calls.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0; 4096];
+                let _ = stream.read(&mut buf).unwrap();
+
+                let idx = counter.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[idx.min(statuses.len() - 1)];
+                let resp = format!(
+                    "HTTP/1.1 {status} TEST\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                );
+                stream.write_all(resp.as_bytes()).unwrap();
+            }
+        });
+
+        (addr, calls)
+    }
+
+    #[test]
+    fn test_transient_status() {
+        assert!(is_transient_status(StatusCode::REQUEST_TIMEOUT));
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_transient_status(StatusCode::OK));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let (addr, calls) = serve(vec![503, 429, 200]);
+        let client = ClientBuilder::new(http_client())
+            .with(RetryMiddleware::new(ConstantBuilder::default()).sleep(no_sleep))
+            .build();
+
+        let resp = client.get(format!("http://{addr}")).send().await.unwrap();
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let (addr, calls) = serve(vec![500]);
+        let client = ClientBuilder::new(http_client())
+            .with(RetryMiddleware::new(ConstantBuilder::default()).sleep(no_sleep))
+            .build();
+
+        let resp = client.get(format!("http://{addr}")).send().await.unwrap();
+
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, resp.status());
+        // `ConstantBuilder` retries 3 times by default.
+        assert_eq!(4, calls.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_client_error() {
+        let (addr, calls) = serve(vec![404, 200]);
+        let client = ClientBuilder::new(http_client())
+            .with(RetryMiddleware::new(ConstantBuilder::default()).sleep(no_sleep))
+            .build();
+
+        let resp = client.get(format!("http://{addr}")).send().await.unwrap();
+
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_retry_connection_error() {
+        // Bind and drop a listener to get a port that refuses connections.
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let client = ClientBuilder::new(http_client())
+            .with(
+                RetryMiddleware::new(ConstantBuilder::default()).sleep(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    ready(())
+                }),
+            )
+            .build();
+
+        let err = client
+            .get(format!("http://{addr}"))
+            .send()
+            .await
+            .unwrap_err();
+
+        assert!(err.is_connect());
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+}
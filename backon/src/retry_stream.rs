@@ -0,0 +1,479 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::ready;
+use core::task::Context;
+use core::task::Poll;
+use core::time::Duration;
+
+use futures_core::Stream;
+
+use crate::backoff::BackoffBuilder;
+use crate::sleep::MaybeSleeper;
+use crate::DefaultSleeper;
+use crate::Sleeper;
+
+/// StreamRetryable adds resubscribe support for functions that produce streams of results.
+///
+/// This means all types that implement `FnMut() -> impl Stream<Item = Result<T, E>>`
+/// will be able to use `retry_stream`.
+///
+/// When the stream yields a retryable error, it's dropped and a new stream is
+/// created by calling the function again after sleeping per the backoff.
+///
+/// # Examples
+///
+/// ```no_run
+/// use core::pin::Pin;
+/// use core::task::Context;
+/// use core::task::Poll;
+///
+/// use anyhow::Result;
+/// use backon::ExponentialBuilder;
+/// use backon::StreamRetryable;
+/// use futures_core::Stream;
+///
+/// struct Events;
+///
+/// impl Stream for Events {
+///     type Item = Result<String>;
+///
+///     fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+///         Poll::Ready(Some(Ok("event".to_string())))
+///     }
+/// }
+///
+/// fn subscribe() -> Events {
+///     Events
+/// }
+///
+/// let events = subscribe
+///     .retry_stream(ExponentialBuilder::default())
+///     // Reset the backoff after 10 events have been received.
+///     .reset_after(10);
+/// ```
+pub trait StreamRetryable<
+    B: BackoffBuilder,
+    T,
+    E,
+    S: Stream<Item = Result<T, E>>,
+    StreamFn: FnMut() -> S,
+>
+{
+    /// Generate a new retry stream.
+    fn retry_stream(self, builder: B) -> RetryStream<B, T, E, S, StreamFn>;
+}
+
+impl<B, T, E, S, StreamFn> StreamRetryable<B, T, E, S, StreamFn> for StreamFn
+where
+    B: BackoffBuilder + Clone,
+    S: Stream<Item = Result<T, E>>,
+    StreamFn: FnMut() -> S,
+{
+    fn retry_stream(self, builder: B) -> RetryStream<B, T, E, S, StreamFn> {
+        RetryStream::new(self, builder)
+    }
+}
+
+/// Struct generated by [`StreamRetryable`].
+pub struct RetryStream<
+    B: BackoffBuilder,
+    T,
+    E,
+    S: Stream<Item = Result<T, E>>,
+    StreamFn: FnMut() -> S,
+    SF: MaybeSleeper = DefaultSleeper,
+    RF = fn(&E) -> bool,
+    NF = fn(&E, Duration),
+> {
+    builder: B,
+    backoff: B::Backoff,
+    stream_fn: StreamFn,
+
+    retryable_fn: RF,
+    notify_fn: NF,
+    sleep_fn: SF,
+
+    reset_after: usize,
+    successes: usize,
+
+    state: State<S, SF::Sleep>,
+}
+
+impl<B, T, E, S, StreamFn> RetryStream<B, T, E, S, StreamFn>
+where
+    B: BackoffBuilder + Clone,
+    S: Stream<Item = Result<T, E>>,
+    StreamFn: FnMut() -> S,
+{
+    /// Initiate a new retry stream.
+    fn new(stream_fn: StreamFn, builder: B) -> Self {
+        RetryStream {
+            backoff: builder.clone().build(),
+            builder,
+            stream_fn,
+
+            retryable_fn: |_: &E| true,
+            notify_fn: |_: &E, _: Duration| {},
+            sleep_fn: DefaultSleeper::default(),
+
+            reset_after: 1,
+            successes: 0,
+
+            state: State::Idle,
+        }
+    }
+}
+
+impl<B, T, E, S, StreamFn, SF, RF, NF> RetryStream<B, T, E, S, StreamFn, SF, RF, NF>
+where
+    B: BackoffBuilder + Clone,
+    S: Stream<Item = Result<T, E>>,
+    StreamFn: FnMut() -> S,
+    SF: MaybeSleeper,
+    RF: FnMut(&E) -> bool,
+    NF: FnMut(&E, Duration),
+{
+    /// Set the sleeper for retrying.
+    ///
+    /// The sleeper should implement the [`Sleeper`] trait. The simplest way is to use a closure that returns a `Future<Output=()>`.
+    ///
+    /// If not specified, we use the [`DefaultSleeper`].
+    pub fn sleep<SN: Sleeper>(self, sleep_fn: SN) -> RetryStream<B, T, E, S, StreamFn, SN, RF, NF> {
+        RetryStream {
+            builder: self.builder,
+            backoff: self.backoff,
+            stream_fn: self.stream_fn,
+            retryable_fn: self.retryable_fn,
+            notify_fn: self.notify_fn,
+            sleep_fn,
+            reset_after: self.reset_after,
+            successes: self.successes,
+            state: State::Idle,
+        }
+    }
+
+    /// Set the conditions for retrying.
+    ///
+    /// If not specified, all errors are considered retryable.
+    ///
+    /// A non-retryable error is yielded to the caller and ends the stream.
+    pub fn when<RN: FnMut(&E) -> bool>(
+        self,
+        retryable: RN,
+    ) -> RetryStream<B, T, E, S, StreamFn, SF, RN, NF> {
+        RetryStream {
+            builder: self.builder,
+            backoff: self.backoff,
+            stream_fn: self.stream_fn,
+            retryable_fn: retryable,
+            notify_fn: self.notify_fn,
+            sleep_fn: self.sleep_fn,
+            reset_after: self.reset_after,
+            successes: self.successes,
+            state: self.state,
+        }
+    }
+
+    /// Set to notify for all retry attempts.
+    ///
+    /// When a retry happens, the input function will be invoked with the error and the sleep duration before pausing.
+    ///
+    /// If not specified, this operation does nothing.
+    pub fn notify<NN: FnMut(&E, Duration)>(
+        self,
+        notify: NN,
+    ) -> RetryStream<B, T, E, S, StreamFn, SF, RF, NN> {
+        RetryStream {
+            builder: self.builder,
+            backoff: self.backoff,
+            stream_fn: self.stream_fn,
+            retryable_fn: self.retryable_fn,
+            notify_fn: notify,
+            sleep_fn: self.sleep_fn,
+            reset_after: self.reset_after,
+            successes: self.successes,
+            state: self.state,
+        }
+    }
+
+    /// Set the number of consecutive successful items after which the backoff is reset.
+    ///
+    /// Once a resubscribed stream has yielded `n` items without an error, the backoff
+    /// is rebuilt from the builder so that the next failure starts from the beginning
+    /// of the backoff again.
+    ///
+    /// If not specified, the backoff is reset after the first successful item. `0` is
+    /// treated as `1` since there is nothing to reset before the first item.
+    pub fn reset_after(mut self, n: usize) -> Self {
+        self.reset_after = n.max(1);
+        self
+    }
+}
+
+/// State maintains internal state of retry stream.
+enum State<S, SleepFut> {
+    Idle,
+    Streaming(S),
+    Sleeping(SleepFut),
+    Done,
+}
+
+impl<B, T, E, S, StreamFn, SF, RF, NF> Stream for RetryStream<B, T, E, S, StreamFn, SF, RF, NF>
+where
+    B: BackoffBuilder + Clone,
+    S: Stream<Item = Result<T, E>>,
+    StreamFn: FnMut() -> S,
+    SF: Sleeper,
+    RF: FnMut(&E) -> bool,
+    NF: FnMut(&E, Duration),
+{
+    type Item = Result<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Safety: This is safe because we don't move the `RetryStream` struct itself,
+        // only its internal state.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    let stream = (this.stream_fn)();
+                    this.state = State::Streaming(stream);
+                    continue;
+                }
+                State::Streaming(stream) => {
+                    // Safety: This is safe because we don't move the `RetryStream` struct and this stream,
+                    // only its internal state.
+                    //
+                    // We do the exactly same thing like `pin_project` but without depending on it directly.
+                    let mut stream = unsafe { Pin::new_unchecked(stream) };
+
+                    match ready!(stream.as_mut().poll_next(cx)) {
+                        None => {
+                            this.state = State::Done;
+                            return Poll::Ready(None);
+                        }
+                        Some(Ok(v)) => {
+                            this.successes = this.successes.saturating_add(1);
+                            if this.successes == this.reset_after {
+                                this.backoff = this.builder.clone().build();
+                            }
+                            return Poll::Ready(Some(Ok(v)));
+                        }
+                        Some(Err(err)) => {
+                            this.successes = 0;
+                            // If input error is not retryable, yield it and end the stream.
+                            if !(this.retryable_fn)(&err) {
+                                this.state = State::Done;
+                                return Poll::Ready(Some(Err(err)));
+                            }
+                            match this.backoff.next() {
+                                None => {
+                                    this.state = State::Done;
+                                    return Poll::Ready(Some(Err(err)));
+                                }
+                                Some(dur) => {
+                                    (this.notify_fn)(&err, dur);
+                                    this.state = State::Sleeping(this.sleep_fn.sleep(dur));
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+                }
+                State::Sleeping(sl) => {
+                    // Safety: This is safe because we don't move the `RetryStream` struct and this fut,
+                    // only its internal state.
+                    //
+                    // We do the exactly same thing like `pin_project` but without depending on it directly.
+                    let mut sl = unsafe { Pin::new_unchecked(sl) };
+
+                    ready!(sl.as_mut().poll(cx));
+                    this.state = State::Idle;
+                    continue;
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::string::String;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::future::poll_fn;
+    use core::future::ready;
+    use core::pin::pin;
+    use core::pin::Pin;
+    use core::task::Context;
+    use core::task::Poll;
+    use core::time::Duration;
+
+    use futures_core::Stream;
+    #[cfg(not(target_arch = "wasm32"))]
+    use tokio::test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::ConstantBuilder;
+
+    /// A stream that yields the items from an iterator.
+    struct IterStream<I>(I);
+
+    impl<I: Iterator + Unpin> Stream for IterStream<I> {
+        type Item = I::Item;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.0.next())
+        }
+    }
+
+    async fn collect<S: Stream>(stream: S) -> Vec<S::Item> {
+        let mut stream = pin!(stream);
+        let mut items = Vec::new();
+        while let Some(item) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            items.push(item);
+        }
+        items
+    }
+
+    #[test]
+    async fn test_retry_stream_resubscribe() {
+        let mut subscriptions = 0;
+        let stream_fn = || {
+            subscriptions += 1;
+            let items: Vec<Result<usize, String>> = if subscriptions < 3 {
+                vec![Ok(subscriptions), Err("disconnected".to_string())]
+            } else {
+                vec![Ok(subscriptions)]
+            };
+            IterStream(items.into_iter())
+        };
+
+        let items = collect(
+            stream_fn
+                .retry_stream(ConstantBuilder::default())
+                .sleep(|_| ready(())),
+        )
+        .await;
+
+        assert_eq!(vec![Ok(1), Ok(2), Ok(3)], items);
+    }
+
+    #[test]
+    async fn test_retry_stream_exhausted() {
+        let mut subscriptions = 0;
+        let stream_fn = || {
+            subscriptions += 1;
+            IterStream(vec![Err::<(), _>("disconnected".to_string())].into_iter())
+        };
+
+        let mut notified = 0;
+        let items = collect(
+            stream_fn
+                .retry_stream(ConstantBuilder::default().with_max_times(2))
+                .sleep(|_| ready(()))
+                .notify(|_, _| notified += 1),
+        )
+        .await;
+
+        assert_eq!(vec![Err("disconnected".to_string())], items);
+        assert_eq!(3, subscriptions);
+        assert_eq!(2, notified);
+    }
+
+    #[test]
+    async fn test_retry_stream_not_retryable() {
+        let mut subscriptions = 0;
+        let stream_fn = || {
+            subscriptions += 1;
+            IterStream(vec![Ok(1), Err("fatal".to_string()), Ok(2)].into_iter())
+        };
+
+        let items = collect(
+            stream_fn
+                .retry_stream(ConstantBuilder::default())
+                .sleep(|_| ready(()))
+                .when(|e| e != "fatal"),
+        )
+        .await;
+
+        assert_eq!(vec![Ok(1), Err("fatal".to_string())], items);
+        assert_eq!(1, subscriptions);
+    }
+
+    #[test]
+    async fn test_retry_stream_reset_after() {
+        // Every subscription yields 2 items before failing, the stream only
+        // ends once the backoff is exhausted.
+        let new_stream_fn = || {
+            let mut subscriptions = 0;
+            move || {
+                subscriptions += 1;
+                IterStream(vec![Ok(()), Ok(()), Err(subscriptions)].into_iter())
+            }
+        };
+
+        // Without reset, the backoff allows only one retry.
+        let items = collect(
+            new_stream_fn()
+                .retry_stream(ConstantBuilder::default().with_max_times(1))
+                .sleep(|_| ready(()))
+                .reset_after(3),
+        )
+        .await;
+        assert_eq!(vec![Ok(()), Ok(()), Ok(()), Ok(()), Err(2)], items);
+
+        // Resetting after 2 items keeps the stream alive, limit it with `when`.
+        let items = collect(
+            new_stream_fn()
+                .retry_stream(ConstantBuilder::default().with_max_times(1))
+                .sleep(|_| ready(()))
+                .when(|e| *e < 3)
+                .reset_after(2),
+        )
+        .await;
+        assert_eq!(
+            vec![Ok(()), Ok(()), Ok(()), Ok(()), Ok(()), Ok(()), Err(3)],
+            items
+        );
+
+        // `0` resets after the first item, like the default.
+        let items = collect(
+            new_stream_fn()
+                .retry_stream(ConstantBuilder::default().with_max_times(1))
+                .sleep(|_| ready(()))
+                .when(|e| *e < 3)
+                .reset_after(0),
+        )
+        .await;
+        assert_eq!(
+            vec![Ok(()), Ok(()), Ok(()), Ok(()), Ok(()), Ok(()), Err(3)],
+            items
+        );
+    }
+
+    #[test]
+    async fn test_retry_stream_notify_duration() {
+        let stream_fn = || IterStream(vec![Err::<(), _>(())].into_iter());
+
+        let mut durations = Vec::new();
+        let _ = collect(
+            stream_fn
+                .retry_stream(ConstantBuilder::default().with_delay(Duration::from_millis(5)))
+                .sleep(|_| ready(()))
+                .notify(|_, dur| durations.push(dur)),
+        )
+        .await;
+
+        assert_eq!(vec![Duration::from_millis(5); 3], durations);
+    }
+}
@@ -1,4 +1,10 @@
+#[cfg(feature = "tracing")]
+use core::fmt::Debug;
+#[cfg(feature = "tracing")]
+use core::fmt::Display;
+use core::future::pending;
 use core::future::Future;
+use core::future::Pending;
 use core::pin::Pin;
 use core::task::ready;
 use core::task::Context;
@@ -6,9 +12,15 @@ use core::task::Poll;
 use core::time::Duration;
 
 use crate::backoff::BackoffBuilder;
+use crate::instrument::GiveUp;
+use crate::instrument::Instrument;
+use crate::limiter::Limit;
 use crate::sleep::MaybeSleeper;
 use crate::Backoff;
 use crate::DefaultSleeper;
+#[cfg(feature = "std")]
+use crate::RetryLimiter;
+use crate::RetryObserver;
 use crate::Sleeper;
 
 /// `RetryableWithContext` adds retry support for functions that produce futures with results
@@ -110,13 +122,25 @@ pub struct RetryWithContext<
     SF: MaybeSleeper = DefaultSleeper,
     RF = fn(&E) -> bool,
     NF = fn(&E, Duration),
+    AF = fn(&E, Option<Duration>) -> Option<Duration>,
+    CF = Pending<()>,
+    OB = (),
 > {
     backoff: B,
     retryable: RF,
     notify: NF,
+    adjust_fn: AF,
     future_fn: FutureFn,
     sleep_fn: SF,
 
+    cancel: CF,
+    /// Set once `cancel` has fired, no more attempts will be started.
+    cancelled: bool,
+
+    instrument: Instrument<E>,
+    limit: Limit,
+    observer: OB,
+
     state: State<T, E, Ctx, Fut, SF::Sleep>,
 }
 
@@ -132,15 +156,21 @@ where
             backoff,
             retryable: |_: &E| true,
             notify: |_: &E, _: Duration| {},
+            adjust_fn: |_: &E, dur: Option<Duration>| dur,
             future_fn,
             sleep_fn: DefaultSleeper::default(),
+            cancel: pending(),
+            cancelled: false,
+            instrument: Instrument::new(),
+            limit: Limit::new(),
+            observer: (),
             state: State::Idle(None),
         }
     }
 }
 
-impl<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF>
-    RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF>
+impl<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
+    RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
 where
     B: Backoff,
     Fut: Future<Output = (Ctx, Result<T, E>)>,
@@ -148,6 +178,7 @@ where
     SF: MaybeSleeper,
     RF: FnMut(&E) -> bool,
     NF: FnMut(&E, Duration),
+    AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
 {
     /// Set the sleeper for retrying.
     ///
@@ -157,7 +188,7 @@ where
     pub fn sleep<SN: Sleeper>(
         self,
         sleep_fn: SN,
-    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SN, RF, NF> {
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SN, RF, NF, AF, CF, OB> {
         assert!(
             matches!(self.state, State::Idle(None)),
             "sleep must be set before context"
@@ -167,8 +198,14 @@ where
             backoff: self.backoff,
             retryable: self.retryable,
             notify: self.notify,
+            adjust_fn: self.adjust_fn,
             future_fn: self.future_fn,
             sleep_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: State::Idle(None),
         }
     }
@@ -179,13 +216,19 @@ where
     pub fn context(
         self,
         context: Ctx,
-    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF> {
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CF, OB> {
         RetryWithContext {
             backoff: self.backoff,
             retryable: self.retryable,
             notify: self.notify,
+            adjust_fn: self.adjust_fn,
             future_fn: self.future_fn,
             sleep_fn: self.sleep_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: State::Idle(Some(context)),
         }
     }
@@ -222,13 +265,19 @@ where
     pub fn when<RN: FnMut(&E) -> bool>(
         self,
         retryable: RN,
-    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RN, NF> {
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RN, NF, AF, CF, OB> {
         RetryWithContext {
             backoff: self.backoff,
             retryable,
             notify: self.notify,
+            adjust_fn: self.adjust_fn,
             future_fn: self.future_fn,
             sleep_fn: self.sleep_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: self.state,
         }
     }
@@ -271,27 +320,139 @@ where
     pub fn notify<NN: FnMut(&E, Duration)>(
         self,
         notify: NN,
-    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NN> {
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NN, AF, CF, OB> {
         RetryWithContext {
             backoff: self.backoff,
             retryable: self.retryable,
             notify,
+            adjust_fn: self.adjust_fn,
+            future_fn: self.future_fn,
+            sleep_fn: self.sleep_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
+            state: self.state,
+        }
+    }
+
+    /// Sets the function to adjust the backoff duration for retry attempts.
+    ///
+    /// See [`Retry::adjust`](crate::Retry::adjust) for details.
+    pub fn adjust<NAF: FnMut(&E, Option<Duration>) -> Option<Duration>>(
+        self,
+        adjust: NAF,
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, NAF, CF, OB> {
+        RetryWithContext {
+            backoff: self.backoff,
+            retryable: self.retryable,
+            notify: self.notify,
+            adjust_fn: adjust,
             future_fn: self.future_fn,
             sleep_fn: self.sleep_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
             state: self.state,
         }
     }
+
+    /// Attach a [`RetryObserver`] to be notified about every step of this retry.
+    ///
+    /// See [`Retry::observe`](crate::Retry::observe) for details.
+    pub fn observe<ON: RetryObserver<E>>(
+        self,
+        observer: ON,
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CF, ON> {
+        RetryWithContext {
+            backoff: self.backoff,
+            retryable: self.retryable,
+            notify: self.notify,
+            adjust_fn: self.adjust_fn,
+            future_fn: self.future_fn,
+            sleep_fn: self.sleep_fn,
+            cancel: self.cancel,
+            cancelled: self.cancelled,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer,
+            state: self.state,
+        }
+    }
+
+    /// Stop retrying once `signal` completes.
+    ///
+    /// An attempt that is already running is allowed to finish, but no more attempts
+    /// are started. If the retry is sleeping, it stops immediately and returns the
+    /// context together with the error that scheduled the retry.
+    ///
+    /// See [`Retry::cancel_on`](crate::Retry::cancel_on) for details.
+    pub fn cancel_on<CN: Future<Output = ()>>(
+        self,
+        signal: CN,
+    ) -> RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CN, OB> {
+        RetryWithContext {
+            backoff: self.backoff,
+            retryable: self.retryable,
+            notify: self.notify,
+            adjust_fn: self.adjust_fn,
+            future_fn: self.future_fn,
+            sleep_fn: self.sleep_fn,
+            cancel: signal,
+            cancelled: false,
+            instrument: self.instrument,
+            limit: self.limit,
+            observer: self.observer,
+            state: self.state,
+        }
+    }
+
+    /// Emit [`tracing`] spans and events for this retry.
+    ///
+    /// See [`Retry::trace`](crate::Retry::trace) for details.
+    #[cfg(feature = "tracing")]
+    pub fn trace(mut self, name: &'static str) -> Self
+    where
+        E: Display + Debug,
+    {
+        self.instrument = self.instrument.with_tracing(name);
+        self
+    }
+
+    /// Record [`metrics`] for this retry, labeled by `policy`.
+    ///
+    /// See [`Retry::metrics`](crate::Retry::metrics) for details.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, policy: impl Into<metrics::SharedString>) -> Self {
+        self.instrument = self.instrument.with_metrics(policy.into());
+        self
+    }
+
+    /// Limit how many retries can be retrying at once with a shared [`RetryLimiter`].
+    ///
+    /// See [`Retry::limit`](crate::Retry::limit) for details.
+    #[cfg(feature = "std")]
+    pub fn limit(mut self, limiter: RetryLimiter) -> Self {
+        self.limit = self.limit.with_limiter(limiter);
+        self
+    }
 }
 
 /// State maintains internal state of retry.
 enum State<T, E, Ctx, Fut: Future<Output = (Ctx, Result<T, E>)>, SleepFut: Future<Output = ()>> {
     Idle(Option<Ctx>),
     Polling(Fut),
-    Sleeping((Option<Ctx>, SleepFut)),
+    /// The error is kept to be returned if the retry is cancelled while sleeping.
+    Sleeping((Option<Ctx>, Option<E>, SleepFut)),
+    /// Waiting for the limiter before the next attempt, the error is kept like `Sleeping`.
+    Waiting((Option<Ctx>, Option<E>)),
 }
 
-impl<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF> Future
-    for RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF>
+impl<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CF, OB> Future
+    for RetryWithContext<B, T, E, Ctx, Fut, FutureFn, SF, RF, NF, AF, CF, OB>
 where
     B: Backoff,
     Fut: Future<Output = (Ctx, Result<T, E>)>,
@@ -299,6 +460,9 @@ where
     SF: Sleeper,
     RF: FnMut(&E) -> bool,
     NF: FnMut(&E, Duration),
+    AF: FnMut(&E, Option<Duration>) -> Option<Duration>,
+    CF: Future<Output = ()>,
+    OB: RetryObserver<E>,
 {
     type Output = (Ctx, Result<T, E>);
 
@@ -309,10 +473,33 @@ where
         // We do the exactly same thing like `pin_project` but without depending on it directly.
         let this = unsafe { self.get_unchecked_mut() };
 
+        if !this.cancelled {
+            // Safety: This is safe because we don't move the `Retry` struct and this fut.
+            //
+            // We do the exactly same thing like `pin_project` but without depending on it directly.
+            let cancel = unsafe { Pin::new_unchecked(&mut this.cancel) };
+            this.cancelled = cancel.poll(cx).is_ready();
+        }
+        let _span = this.instrument.enter();
+        if this.cancelled {
+            // Stop sleeping and return the error that scheduled this retry.
+            if let State::Sleeping((ctx, err, _)) | State::Waiting((ctx, err)) = &mut this.state {
+                let ctx = ctx.take().expect("context must be valid");
+                let err = err.take().expect("error must be valid");
+                this.state = State::Idle(None);
+                this.limit.release();
+                this.instrument.give_up(&err, GiveUp::Cancelled);
+                this.observer.on_give_up(this.instrument.attempt(), &err);
+                return Poll::Ready((ctx, Err(err)));
+            }
+        }
+
         loop {
             match &mut this.state {
                 State::Idle(ctx) => {
                     let ctx = ctx.take().expect("context must be valid");
+                    this.instrument.attempt_start();
+                    this.observer.on_attempt_start(this.instrument.attempt());
                     let fut = (this.future_fn)(ctx);
                     this.state = State::Polling(fut);
                     continue;
@@ -324,27 +511,59 @@ where
                     // We do the exactly same thing like `pin_project` but without depending on it directly.
                     let mut fut = unsafe { Pin::new_unchecked(fut) };
 
-                    let (ctx, res) = ready!(fut.as_mut().poll(cx));
+                    let (ctx, res) = {
+                        let _attempt = this.instrument.enter_attempt();
+                        ready!(fut.as_mut().poll(cx))
+                    };
+                    let attempt = this.instrument.attempt();
                     match res {
-                        Ok(v) => return Poll::Ready((ctx, Ok(v))),
+                        Ok(v) => {
+                            this.instrument.success();
+                            this.observer.on_success(attempt);
+                            this.limit.release();
+                            return Poll::Ready((ctx, Ok(v)));
+                        }
                         Err(err) => {
-                            // If input error is not retryable, return error directly.
+                            this.observer.on_attempt_error(attempt, &err);
+                            // If input error is not retryable or retry has been cancelled,
+                            // return error directly.
+                            if this.cancelled {
+                                this.instrument.give_up(&err, GiveUp::Cancelled);
+                                this.observer.on_give_up(attempt, &err);
+                                this.limit.release();
+                                return Poll::Ready((ctx, Err(err)));
+                            }
                             if !(this.retryable)(&err) {
+                                this.instrument.give_up(&err, GiveUp::NotRetryable);
+                                this.observer.on_give_up(attempt, &err);
+                                this.limit.release();
                                 return Poll::Ready((ctx, Err(err)));
                             }
-                            match this.backoff.next() {
-                                None => return Poll::Ready((ctx, Err(err))),
+                            match (this.adjust_fn)(&err, this.backoff.next()) {
+                                None => {
+                                    this.instrument.give_up(&err, GiveUp::Exhausted);
+                                    this.observer.on_give_up(attempt, &err);
+                                    this.limit.release();
+                                    return Poll::Ready((ctx, Err(err)));
+                                }
+                                Some(_) if !this.limit.admit() => {
+                                    this.instrument.give_up(&err, GiveUp::Limited);
+                                    this.observer.on_give_up(attempt, &err);
+                                    return Poll::Ready((ctx, Err(err)));
+                                }
                                 Some(dur) => {
+                                    this.instrument.retry(&err, dur);
+                                    this.observer.on_retry_scheduled(attempt, &err, dur);
                                     (this.notify)(&err, dur);
-                                    this.state =
-                                        State::Sleeping((Some(ctx), this.sleep_fn.sleep(dur)));
+                                    let sl = this.sleep_fn.sleep(dur);
+                                    this.state = State::Sleeping((Some(ctx), Some(err), sl));
                                     continue;
                                 }
                             }
                         }
                     }
                 }
-                State::Sleeping((ctx, sl)) => {
+                State::Sleeping((ctx, err, sl)) => {
                     // Safety: This is safe because we don't move the `Retry` struct and this fut,
                     // only its internal state.
                     //
@@ -352,6 +571,11 @@ where
                     let mut sl = unsafe { Pin::new_unchecked(sl) };
 
                     ready!(sl.as_mut().poll(cx));
+                    this.state = State::Waiting((ctx.take(), err.take()));
+                    continue;
+                }
+                State::Waiting((ctx, _)) => {
+                    ready!(this.limit.poll_wait(cx));
                     let ctx = ctx.take().expect("context must be valid");
                     this.state = State::Idle(Some(ctx));
                     continue;
@@ -362,7 +586,11 @@ where
 }
 
 #[cfg(test)]
-#[cfg(any(feature = "tokio-sleep", feature = "gloo-timers-sleep",))]
+#[cfg(any(
+    feature = "tokio-sleep",
+    feature = "gloo-timers-sleep",
+    feature = "async-io-sleep",
+))]
 mod tests {
     extern crate alloc;
 
@@ -417,4 +645,39 @@ mod tests {
         // only once.
         assert_eq!(*error_times.lock().await, 1);
     }
+
+    #[test]
+    async fn test_retry_with_adjust() {
+        let (attempts, result) =
+            { |attempts: usize| async move { (attempts + 1, Err::<(), _>(anyhow!("retryable"))) } }
+                .retry(ExponentialBuilder::default())
+                .sleep(|_| core::future::ready(()))
+                // Stop retrying after the second attempt.
+                .adjust(|_, dur| dur.filter(|dur| *dur < Duration::from_secs(2)))
+                .context(0)
+                .await;
+
+        assert_eq!(2, attempts);
+        assert_eq!("retryable", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    async fn test_retry_cancel_while_sleeping() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+        let retry =
+            { |attempts: usize| async move { (attempts + 1, Err::<(), _>(anyhow!("retryable"))) } }
+                .retry(ExponentialBuilder::default())
+                // Sleep forever, only cancellation can stop the retry.
+                .sleep(|_| pending::<()>())
+                .context(0)
+                .cancel_on(async {
+                    let _ = rx.await;
+                });
+
+        let ((attempts, result), _) = tokio::join!(retry, async { tx.send(()).unwrap() });
+
+        assert_eq!(1, attempts);
+        assert_eq!("retryable", result.unwrap_err().to_string());
+    }
 }
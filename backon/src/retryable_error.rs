@@ -0,0 +1,88 @@
+use core::time::Duration;
+
+/// RetryableError classifies errors into transient and permanent ones.
+///
+/// Implement this trait for your error type once, then call `when_retryable`
+/// on [`Retry`](crate::Retry) or [`BlockingRetry`](crate::BlockingRetry)
+/// instead of repeating the same `when` closure at every call site.
+///
+/// With the `macros` feature enabled, `RetryableError` can also be derived for
+/// enums whose variants are marked with `#[transient]` or `#[permanent]`.
+/// Unmarked variants are considered permanent.
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use backon::RetryableError;
+///
+/// #[derive(Debug)]
+/// enum Error {
+///     RateLimited(Duration),
+///     Unavailable,
+///     NotFound,
+/// }
+///
+/// impl RetryableError for Error {
+///     fn is_retryable(&self) -> bool {
+///         matches!(self, Error::RateLimited(_) | Error::Unavailable)
+///     }
+///
+///     fn retry_after(&self) -> Option<Duration> {
+///         match self {
+///             Error::RateLimited(dur) => Some(*dur),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// assert!(Error::Unavailable.is_retryable());
+/// assert!(!Error::NotFound.is_retryable());
+/// ```
+pub trait RetryableError {
+    /// Returns `true` if the operation that failed with this error is worth retrying.
+    fn is_retryable(&self) -> bool;
+
+    /// Returns the delay requested by this error before the next attempt.
+    ///
+    /// If `None` is returned, the delay from the backoff is used.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Use the delay requested by the error, but only if the backoff allows another attempt.
+pub(crate) fn adjust<E: RetryableError>(err: &E, dur: Option<Duration>) -> Option<Duration> {
+    dur.map(|dur| err.retry_after().unwrap_or(dur))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Transient(Option<Duration>);
+
+    impl RetryableError for Transient {
+        fn is_retryable(&self) -> bool {
+            true
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_adjust() {
+        let dur = Some(Duration::from_secs(1));
+
+        assert_eq!(dur, adjust(&Transient(None), dur));
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            adjust(&Transient(Some(Duration::from_secs(5))), dur)
+        );
+        // The backoff is exhausted, `retry_after` must not extend it.
+        assert_eq!(None, adjust(&Transient(Some(Duration::from_secs(5))), None));
+    }
+}
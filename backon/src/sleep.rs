@@ -1,5 +1,11 @@
 use core::future::Future;
 use core::future::Ready;
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+use core::pin::Pin;
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+use core::task::Context;
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+use core::task::Poll;
 use core::time::Duration;
 
 /// A sleeper is used to generate a future that completes after a specified duration.
@@ -35,13 +41,26 @@ impl<F: Fn(Duration) -> Fut + 'static, Fut: Future<Output = ()>> Sleeper for F {
 /// The default implementation of `Sleeper` when no features are enabled.
 ///
 /// It will fail to compile if a containing [`Retry`][crate::Retry] is `.await`ed without calling [`Retry::sleep`][crate::Retry::sleep] to provide a valid sleeper.
-#[cfg(all(not(feature = "tokio-sleep"), not(feature = "gloo-timers-sleep"),))]
+#[cfg(not(any(
+    all(not(target_arch = "wasm32"), feature = "tokio-sleep"),
+    all(not(target_arch = "wasm32"), feature = "async-io-sleep"),
+    all(target_arch = "wasm32", feature = "gloo-timers-sleep"),
+)))]
 pub type DefaultSleeper = PleaseEnableAFeatureOrProvideACustomSleeper;
 /// The default implementation of `Sleeper` while feature `tokio-sleep` enabled.
 ///
 /// it uses `tokio::time::sleep`.
 #[cfg(all(not(target_arch = "wasm32"), feature = "tokio-sleep"))]
 pub type DefaultSleeper = TokioSleeper;
+/// The default implementation of `Sleeper` while feature `async-io-sleep` enabled and `tokio-sleep` disabled.
+///
+/// It uses `async_io::Timer`.
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    not(feature = "tokio-sleep"),
+    feature = "async-io-sleep"
+))]
+pub type DefaultSleeper = SmolSleeper;
 /// The default implementation of `Sleeper` while feature `gloo-timers-sleep` enabled.
 ///
 /// It uses `gloo_timers::sleep::sleep`.
@@ -53,6 +72,7 @@ pub type DefaultSleeper = GlooTimersSleep;
 /// Users should enable a feature of this crate that provides a valid [`Sleeper`] implementation when this type appears in compilation errors. Alternatively, a custom [`Sleeper`] implementation should be provided where necessary, such as in [`crate::Retry::sleeper`].
 #[doc(hidden)]
 #[derive(Clone, Copy, Debug, Default)]
+#[allow(dead_code)]
 pub struct PleaseEnableAFeatureOrProvideACustomSleeper;
 
 /// Implement `MaybeSleeper` but not `Sleeper`.
@@ -77,6 +97,37 @@ impl Sleeper for TokioSleeper {
     }
 }
 
+/// The implementation of `Sleeper` that uses `async_io::Timer`.
+///
+/// This implementation is based on the [`async-io`](https://docs.rs/async-io/latest/async_io/)
+/// crate, which powers `smol` and `async-std`. It works with any executor.
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SmolSleeper;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+impl Sleeper for SmolSleeper {
+    type Sleep = SmolTimer;
+
+    fn sleep(&self, dur: Duration) -> Self::Sleep {
+        SmolTimer(async_io::Timer::after(dur))
+    }
+}
+
+/// The future returned by [`SmolSleeper`].
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+#[derive(Debug)]
+pub struct SmolTimer(async_io::Timer);
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "async-io-sleep"))]
+impl Future for SmolTimer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|_| ())
+    }
+}
+
 /// The implementation of `Sleeper` that uses `futures_timer::Delay`.
 ///
 /// This implementation is based on
@@ -0,0 +1,338 @@
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::ready;
+use core::task::Context;
+use core::task::Poll;
+
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::backoff::BackoffBuilder;
+use crate::sleep::MaybeSleeper;
+use crate::Backoff;
+use crate::DefaultSleeper;
+use crate::Sleeper;
+
+/// BackonRetryLayer is a [`tower_layer::Layer`] that retries requests with backon backoffs.
+///
+/// Every request is cloned before it's sent, and re-issued after sleeping
+/// if the classifier decides the result is retryable. A new backoff is built
+/// from the builder for every request.
+///
+/// The classifier is a function over `&Result<Response, Error>` that returns
+/// `true` if the request should be retried. This allows retrying on both
+/// service errors and failed responses like `503 Service Unavailable`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use backon::BackonRetryLayer;
+/// use backon::ExponentialBuilder;
+///
+/// #[derive(Debug)]
+/// struct Response {
+///     status: u16,
+/// }
+///
+/// let layer = BackonRetryLayer::new(
+///     ExponentialBuilder::default(),
+///     |res: &Result<Response, std::io::Error>| match res {
+///         Ok(resp) => resp.status >= 500,
+///         Err(_) => true,
+///     },
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct BackonRetryLayer<B: BackoffBuilder, P, SF: MaybeSleeper = DefaultSleeper> {
+    builder: B,
+    policy: P,
+    sleep_fn: SF,
+}
+
+impl<B, P> BackonRetryLayer<B, P>
+where
+    B: BackoffBuilder,
+{
+    /// Create a new retry layer with the given backoff builder and classifier.
+    pub fn new(builder: B, policy: P) -> Self {
+        BackonRetryLayer {
+            builder,
+            policy,
+            sleep_fn: DefaultSleeper::default(),
+        }
+    }
+}
+
+impl<B, P, SF> BackonRetryLayer<B, P, SF>
+where
+    B: BackoffBuilder,
+    SF: MaybeSleeper,
+{
+    /// Set the sleeper for retrying.
+    ///
+    /// The sleeper should implement the [`Sleeper`] trait and be cloneable since
+    /// every service created by this layer owns a copy.
+    ///
+    /// If not specified, we use the [`DefaultSleeper`].
+    pub fn sleep<SN: Sleeper>(self, sleep_fn: SN) -> BackonRetryLayer<B, P, SN> {
+        BackonRetryLayer {
+            builder: self.builder,
+            policy: self.policy,
+            sleep_fn,
+        }
+    }
+}
+
+impl<S, B, P, SF> Layer<S> for BackonRetryLayer<B, P, SF>
+where
+    B: BackoffBuilder + Clone,
+    P: Clone,
+    SF: MaybeSleeper + Clone,
+{
+    type Service = BackonRetryService<S, B, P, SF>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BackonRetryService {
+            inner,
+            builder: self.builder.clone(),
+            policy: self.policy.clone(),
+            sleep_fn: self.sleep_fn.clone(),
+        }
+    }
+}
+
+/// Service generated by [`BackonRetryLayer`].
+#[derive(Clone, Debug)]
+pub struct BackonRetryService<S, B: BackoffBuilder, P, SF: MaybeSleeper = DefaultSleeper> {
+    inner: S,
+    builder: B,
+    policy: P,
+    sleep_fn: SF,
+}
+
+impl<S, Req, B, P, SF> Service<Req> for BackonRetryService<S, B, P, SF>
+where
+    S: Service<Req> + Clone,
+    Req: Clone,
+    B: BackoffBuilder + Clone,
+    P: FnMut(&Result<S::Response, S::Error>) -> bool + Clone,
+    SF: Sleeper + Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BackonRetryFuture<S, Req, B::Backoff, P, SF>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        // The inner service has been driven to readiness, use it for the first
+        // attempt and leave a fresh clone behind for the following requests.
+        let clone = self.inner.clone();
+        let mut inner = mem::replace(&mut self.inner, clone);
+        let fut = inner.call(req.clone());
+
+        BackonRetryFuture {
+            inner,
+            request: req,
+            backoff: self.builder.clone().build(),
+            policy: self.policy.clone(),
+            sleep_fn: self.sleep_fn.clone(),
+            state: State::Polling(fut),
+        }
+    }
+}
+
+/// Future returned by [`BackonRetryService`].
+pub struct BackonRetryFuture<S, Req, B, P, SF>
+where
+    S: Service<Req>,
+    B: Backoff,
+    SF: Sleeper,
+{
+    inner: S,
+    request: Req,
+    backoff: B,
+    policy: P,
+    sleep_fn: SF,
+
+    state: State<S::Future, SF::Sleep>,
+}
+
+/// State maintains internal state of retry.
+enum State<Fut, SleepFut> {
+    Polling(Fut),
+    Sleeping(SleepFut),
+    Ready,
+}
+
+impl<S, Req, B, P, SF> Future for BackonRetryFuture<S, Req, B, P, SF>
+where
+    S: Service<Req>,
+    Req: Clone,
+    B: Backoff,
+    P: FnMut(&Result<S::Response, S::Error>) -> bool,
+    SF: Sleeper,
+{
+    type Output = Result<S::Response, S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: This is safe because we don't move the `BackonRetryFuture` struct itself,
+        // only its internal state.
+        //
+        // We do the exactly same thing like `pin_project` but without depending on it directly.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        loop {
+            match &mut this.state {
+                State::Polling(fut) => {
+                    // Safety: This is safe because we don't move the `BackonRetryFuture` struct and this fut,
+                    // only its internal state.
+                    //
+                    // We do the exactly same thing like `pin_project` but without depending on it directly.
+                    let mut fut = unsafe { Pin::new_unchecked(fut) };
+
+                    let res = ready!(fut.as_mut().poll(cx));
+                    // If the result is not retryable, return it directly.
+                    if !(this.policy)(&res) {
+                        return Poll::Ready(res);
+                    }
+                    match this.backoff.next() {
+                        None => return Poll::Ready(res),
+                        Some(dur) => {
+                            this.state = State::Sleeping(this.sleep_fn.sleep(dur));
+                            continue;
+                        }
+                    }
+                }
+                State::Sleeping(sl) => {
+                    // Safety: This is safe because we don't move the `BackonRetryFuture` struct and this fut,
+                    // only its internal state.
+                    //
+                    // We do the exactly same thing like `pin_project` but without depending on it directly.
+                    let mut sl = unsafe { Pin::new_unchecked(sl) };
+
+                    ready!(sl.as_mut().poll(cx));
+                    this.state = State::Ready;
+                    continue;
+                }
+                State::Ready => {
+                    ready!(this.inner.poll_ready(cx))?;
+                    let fut = this.inner.call(this.request.clone());
+                    this.state = State::Polling(fut);
+                    continue;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::future::ready;
+    use core::future::Ready;
+    use core::task::Context;
+    use core::task::Poll;
+    use core::time::Duration;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use tokio::test;
+    use tower_layer::Layer;
+    use tower_service::Service;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use crate::BackonRetryLayer;
+    use crate::ConstantBuilder;
+
+    /// A service that fails until it has been called `succeed_after` times.
+    #[derive(Clone)]
+    struct FlakyService {
+        calls: Arc<AtomicUsize>,
+        succeed_after: usize,
+    }
+
+    impl Service<&'static str> for FlakyService {
+        type Response = &'static str;
+        type Error = &'static str;
+        type Future = Ready<Result<&'static str, &'static str>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: &'static str) -> Self::Future {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if calls >= self.succeed_after {
+                ready(Ok(req))
+            } else {
+                ready(Err("unavailable"))
+            }
+        }
+    }
+
+    fn no_sleep(_: Duration) -> Ready<()> {
+        ready(())
+    }
+
+    fn is_err(res: &Result<&'static str, &'static str>) -> bool {
+        res.is_err()
+    }
+
+    #[test]
+    async fn test_retry_until_success() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut svc = BackonRetryLayer::new(ConstantBuilder::default(), is_err)
+            .sleep(no_sleep)
+            .layer(FlakyService {
+                calls: calls.clone(),
+                succeed_after: 3,
+            });
+
+        let result = svc.call("hello").await;
+
+        assert_eq!(Ok("hello"), result);
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    async fn test_retry_exhausted() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut svc = BackonRetryLayer::new(ConstantBuilder::default(), is_err)
+            .sleep(no_sleep)
+            .layer(FlakyService {
+                calls: calls.clone(),
+                succeed_after: usize::MAX,
+            });
+
+        let result = svc.call("hello").await;
+
+        assert_eq!(Err("unavailable"), result);
+        // `ConstantBuilder` retries 3 times by default.
+        assert_eq!(4, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    async fn test_retry_not_retryable() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut svc = BackonRetryLayer::new(
+            ConstantBuilder::default(),
+            |_: &Result<&'static str, &'static str>| false,
+        )
+        .sleep(no_sleep)
+        .layer(FlakyService {
+            calls: calls.clone(),
+            succeed_after: usize::MAX,
+        });
+
+        let result = svc.call("hello").await;
+
+        assert_eq!(Err("unavailable"), result);
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+}